@@ -0,0 +1,118 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use next_core::next_font_bench::{format_fallback_font_name, has_font_metrics};
+
+/// Font names covering the shapes of Google font family names
+static FONT_NAME_CORPUS: &[&str] = &[
+    "Inter",
+    "Roboto Slab",
+    "ABeeZee",
+    "M PLUS 1p",
+    "Noto Sans JP",
+    "IBM Plex Mono",
+    "Zen Kaku Gothic New",
+    "Baloo Bhai 2",
+    "Source Code Pro",
+    "Ma Shan Zheng",
+    " Leading Space",
+    "lower case",
+    "Open_Sans",
+    "-Prefixed",
+];
+
+/// The system allocator, counting allocations to compare how much lookups
+/// allocate along with how long they take.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// The number of allocations made by `f`
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_format_fallback_font_name(c: &mut Criterion) {
+    let mut group = c.benchmark_group("format_fallback_font_name");
+    group.throughput(Throughput::Elements(FONT_NAME_CORPUS.len() as u64));
+    group.bench_function("corpus", |b| {
+        b.iter(|| {
+            for font_family in FONT_NAME_CORPUS {
+                black_box(format_fallback_font_name(black_box(font_family)));
+            }
+        })
+    });
+    group.finish();
+}
+
+/// A metrics file with entries for numbered copies of the fonts of
+/// [FONT_NAME_CORPUS], to approach the size of the bundled metrics file.
+fn metrics_json() -> String {
+    let entries: Vec<String> = (0..50)
+        .flat_map(|copy| {
+            FONT_NAME_CORPUS.iter().map(move |font_family| {
+                let font_family = format!("{} {}", font_family, copy);
+                format!(
+                    r#""{}": {{"familyName": "{}", "category": "sans-serif", "capHeight": 1456, "ascent": 2146, "descent": -555, "lineGap": 0, "unitsPerEm": 2048, "xHeight": 1082, "xWidthAvg": 969}}"#,
+                    format_fallback_font_name(&font_family),
+                    font_family
+                )
+            })
+        })
+        .collect();
+    format!("{{{}}}", entries.join(","))
+}
+
+fn bench_single_font_metrics_lookup(c: &mut Criterion) {
+    let metrics_json = metrics_json();
+    let font_family = "Inter 49";
+    let [whole_map_allocations, lazy_allocations] = [false, true].map(|lazily| {
+        count_allocations(|| {
+            assert!(
+                has_font_metrics(&metrics_json, font_family, lazily).unwrap(),
+                "`{}` is missing from the metrics",
+                font_family
+            );
+        })
+    });
+    assert!(
+        lazy_allocations < whole_map_allocations,
+        "the lazy lookup allocates as much as parsing the whole map"
+    );
+
+    let mut group = c.benchmark_group("single_font_metrics_lookup");
+    group.throughput(Throughput::Bytes(metrics_json.len() as u64));
+    group.bench_function("whole_map", |b| {
+        b.iter(|| has_font_metrics(black_box(&metrics_json), black_box(font_family), false))
+    });
+    group.bench_function("lazily", |b| {
+        b.iter(|| has_font_metrics(black_box(&metrics_json), black_box(font_family), true))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_format_fallback_font_name,
+    bench_single_font_metrics_lookup
+);
+criterion_main!(benches);
@@ -35,7 +35,27 @@ mod transform_options;
 mod util;
 mod web_entry_source;
 
+#[doc(hidden)]
+pub mod next_font_bench {
+    //! Internals of next/font that are benchmarked in `benches/`.
+    pub use crate::next_font::google::font_fallback::format_fallback_font_name;
+
+    /// Whether `font_family` has metrics in `metrics_json`, parsing either the
+    /// whole metrics map or only the font's entry if `lazily`
+    pub fn has_font_metrics(
+        metrics_json: &str,
+        font_family: &str,
+        lazily: bool,
+    ) -> anyhow::Result<bool> {
+        crate::next_font::google::font_fallback::has_font_metrics(metrics_json, font_family, lazily)
+    }
+}
+
 pub use app_source::create_app_source;
+pub use next_font::{
+    font_fallback::{default_fallback_fonts, DefaultFallbackFont},
+    issue::{JsonIssueReporterVc, NextFontCheckProcessorVc},
+};
 pub use page_source::create_page_source;
 pub use turbo_binding::{turbopack::node::source_map, *};
 pub use web_entry_source::create_web_entry_source;
@@ -1,13 +1,35 @@
-use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use anyhow::{bail, Result};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use turbo_binding::turbo::tasks::{
-    primitives::{StringVc, StringsVc},
-    trace::TraceRawVcs,
+use turbo_binding::{
+    turbo::{
+        tasks::{
+            primitives::{StringVc, StringsVc, U64Vc},
+            trace::TraceRawVcs,
+        },
+        tasks_hash::hash_xxh3_hash64,
+    },
+    turbopack::core::issue::IssueSeverity,
 };
 
-pub(crate) struct DefaultFallbackFont {
+use super::{issue::NextFontsIssue, util::CssFormat};
+
+/// A local font automatic fallbacks are generated with, along with the
+/// metrics they are adjusted against
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultFallbackFont {
     pub name: String,
+    /// The generic CSS font family this font is a typical choice for
+    pub generic_family: String,
+    /// Vertical metrics in font units, as published by
+    /// [capsize](https://github.com/seek-oss/capsize/tree/master/packages/metrics)
+    pub ascent: i32,
+    pub descent: i32,
+    pub line_gap: u32,
+    pub x_height: i32,
     pub x_width_avg: f64,
     pub units_per_em: u32,
 }
@@ -16,6 +38,11 @@ pub(crate) struct DefaultFallbackFont {
 pub(crate) static DEFAULT_SANS_SERIF_FONT: Lazy<DefaultFallbackFont> =
     Lazy::new(|| DefaultFallbackFont {
         name: "Arial".to_owned(),
+        generic_family: "sans-serif".to_owned(),
+        ascent: 1854,
+        descent: -434,
+        line_gap: 67,
+        x_height: 1062,
         x_width_avg: 934.5116279069767,
         units_per_em: 2048,
     });
@@ -23,10 +50,58 @@ pub(crate) static DEFAULT_SANS_SERIF_FONT: Lazy<DefaultFallbackFont> =
 pub(crate) static DEFAULT_SERIF_FONT: Lazy<DefaultFallbackFont> =
     Lazy::new(|| DefaultFallbackFont {
         name: "Times New Roman".to_owned(),
+        generic_family: "serif".to_owned(),
+        ascent: 1825,
+        descent: -443,
+        line_gap: 87,
+        x_height: 916,
         x_width_avg: 854.3953488372093,
         units_per_em: 2048,
     });
 
+impl DefaultFallbackFont {
+    /// The `size_adjust` needed for this font to match the average character
+    /// width of a webfont, given as `x_width_avg` in `units_per_em` font units.
+    pub(crate) fn size_adjust_for(&self, x_width_avg: f64, units_per_em: f64) -> f64 {
+        let main_font_avg_width = x_width_avg / units_per_em;
+        let fallback_font_avg_width = self.x_width_avg / self.units_per_em as f64;
+        main_font_avg_width / fallback_font_avg_width
+    }
+
+    /// The `size_adjust` needed for this font to match the x-height of a
+    /// webfont, given as `x_height` in `units_per_em` font units.
+    pub(crate) fn x_height_size_adjust_for(&self, x_height: f64, units_per_em: f64) -> f64 {
+        (x_height / units_per_em) / (self.x_height as f64 / self.units_per_em as f64)
+    }
+
+    /// The adjustment that leaves this font's rendering as is: its own
+    /// metrics with a `size_adjust` of 1.
+    pub(crate) fn unadjusted(&self) -> FontAdjustment {
+        let units_per_em = self.units_per_em as f64;
+        FontAdjustment {
+            ascent: self.ascent as f64 / units_per_em,
+            descent: self.descent as f64 / units_per_em,
+            line_gap: self.line_gap as f64 / units_per_em,
+            size_adjust: 1.0,
+        }
+    }
+}
+
+/// The default fallback font named `local_font_family`, e.g. `Arial`
+pub(crate) fn find_default_fallback_font(
+    local_font_family: &str,
+) -> Option<&'static DefaultFallbackFont> {
+    default_fallback_fonts()
+        .into_iter()
+        .find(|font| font.name == local_font_family)
+}
+
+/// All fonts automatic fallbacks can be generated for, with the metrics they
+/// are compared against, e.g. for tooling showing why a fallback was chosen
+pub fn default_fallback_fonts() -> [&'static DefaultFallbackFont; 2] {
+    [&DEFAULT_SANS_SERIF_FONT, &DEFAULT_SERIF_FONT]
+}
+
 /// An automatically generated fallback font generated by next/font.
 #[turbo_tasks::value(shared)]
 pub(crate) struct AutomaticFontFallback {
@@ -35,6 +110,37 @@ pub(crate) struct AutomaticFontFallback {
     /// The name of font locally, used in `src: local("{}")`
     pub local_font_family: StringVc,
     pub adjustment: Option<FontAdjustment>,
+    /// The full-precision value of `adjustment`, if it was kept for debugging
+    /// after rounding `adjustment` as it is rendered in CSS.
+    pub precise_adjustment: Option<FontAdjustment>,
+    /// The most bytes the fallback's `@font-face` rule may take, dropping its
+    /// least impactful overrides when exceeded.
+    pub css_byte_budget: Option<u32>,
+    /// Whether the adjusted `@font-face` is declared in a
+    /// `@supports (size-adjust: 100%)` rule, after an unadjusted one for
+    /// browsers without `size-adjust`.
+    pub size_adjust_supports_guard: bool,
+    /// The lowest and highest weight of a fallback declared once for all of
+    /// a font's weights, e.g. `(400, 700)`. `None` for a fallback of any
+    /// weight.
+    pub font_weight_range: Option<(u16, u16)>,
+    /// A css custom property (css variable), e.g. `--font-inter-fallback`,
+    /// assigned the scoped font family in the `variable` class
+    pub variable: Option<String>,
+    /// The named instance of a variable font the fallback stands in for,
+    /// e.g. `Bold`, rendered as `font-named-instance`
+    pub named_instance: Option<String>,
+    /// How to lay out the fallback's CSS, if not as generated
+    pub css_format: Option<CssFormat>,
+    /// Whether the override values are also emitted as css custom properties,
+    /// see [[build_override_custom_properties]]
+    pub debug_custom_properties: bool,
+    /// Whether the `@font-face` also contains the override values of the
+    /// local font without adjustment in a comment
+    pub debug_unadjusted_overrides: bool,
+    /// Font families listed after the fallback's scoped font family, e.g. a
+    /// font stack composed with the fallback
+    pub font_stack: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -49,15 +155,158 @@ pub(crate) enum FontFallback {
     Error,
     /// A list of manually provided font names to use a fallback, as-is.
     Manual(StringsVc),
+    /// No fallback is generated, e.g. for color or emoji fonts that a text
+    /// fallback font can't approximate.
+    Skipped,
+}
+
+#[turbo_tasks::value_impl]
+impl FontFallbackVc {
+    /// A stable hash of everything rendered for the fallback, for consumers
+    /// caching CSS keyed on it. The same across builds and machines. Reported
+    /// in the fallback preview, see `NextFontGoogleOptions::fallback_preview`.
+    #[turbo_tasks::function]
+    pub(crate) async fn content_hash(self) -> Result<U64Vc> {
+        let content = match &*self.await? {
+            FontFallback::Automatic(fallback) => {
+                let fallback = fallback.await?;
+                FontFallbackContent::Automatic {
+                    scoped_font_family: fallback.scoped_font_family.await?.clone_value(),
+                    local_font_family: fallback.local_font_family.await?.clone_value(),
+                    adjustment: fallback.adjustment.clone(),
+                    css_byte_budget: fallback.css_byte_budget,
+                    size_adjust_supports_guard: fallback.size_adjust_supports_guard,
+                    font_weight_range: fallback.font_weight_range,
+                    variable: fallback.variable.clone(),
+                    named_instance: fallback.named_instance.clone(),
+                    css_format: fallback.css_format,
+                    debug_custom_properties: fallback.debug_custom_properties,
+                    debug_unadjusted_overrides: fallback.debug_unadjusted_overrides,
+                    font_stack: fallback.font_stack.clone(),
+                }
+            }
+            FontFallback::Error => FontFallbackContent::Error,
+            FontFallback::Manual(fonts) => FontFallbackContent::Manual(fonts.await?.clone_value()),
+            FontFallback::Skipped => FontFallbackContent::Skipped,
+        };
+
+        Ok(U64Vc::cell(hash_font_fallback_content(&content)?))
+    }
+}
+
+/// The resolved values of a [[FontFallback]] that affect its CSS, hashed by
+/// [[FontFallbackVc::content_hash]]
+#[derive(Serialize)]
+enum FontFallbackContent {
+    Automatic {
+        scoped_font_family: String,
+        local_font_family: String,
+        adjustment: Option<FontAdjustment>,
+        css_byte_budget: Option<u32>,
+        size_adjust_supports_guard: bool,
+        font_weight_range: Option<(u16, u16)>,
+        variable: Option<String>,
+        named_instance: Option<String>,
+        css_format: Option<CssFormat>,
+        debug_custom_properties: bool,
+        debug_unadjusted_overrides: bool,
+        font_stack: Vec<String>,
+    },
+    Error,
+    Manual(Vec<String>),
+    Skipped,
+}
+
+/// Hashes the JSON serialization of `content`, which is deterministic as
+/// floats are serialized to their shortest exact representation.
+fn hash_font_fallback_content(content: &FontFallbackContent) -> Result<u64> {
+    Ok(hash_xxh3_hash64(serde_json::to_string(content)?))
 }
 
 #[turbo_tasks::value(transparent)]
 pub(crate) struct FontFallbacks(Vec<FontFallbackVc>);
 
+/// Checks that every automatic fallback of a chunk has its own scoped font
+/// family, given the scoped font family of each font request along with the
+/// request's hash, returning the scoped font families declared by more than
+/// one request. Colliding `@font-face` rules override each other. Run by
+/// [[check_next_fonts]].
+pub(crate) fn find_scoped_font_family_collisions<'a>(
+    fallbacks: impl IntoIterator<Item = (&'a str, u32)>,
+) -> BTreeSet<String> {
+    let requests = fallbacks.into_iter().collect::<BTreeSet<_>>();
+    find_duplicates(
+        requests
+            .into_iter()
+            .map(|(scoped_font_family, _)| scoped_font_family.to_owned()),
+    )
+}
+
+/// Finds the metrics keys that distinct font families are looked up by, e.g.
+/// `robotoSlab` for both `Roboto Slab` and `RobotoSlab`, given each font
+/// family along with its metrics key. Such font families silently share the
+/// same metrics. Returns the font families of each such key, sorted by key.
+/// Run over the requested fonts by [[check_next_fonts]].
+pub(crate) fn find_metrics_key_collisions<'a>(
+    font_families: impl IntoIterator<Item = (String, &'a str)>,
+) -> BTreeMap<String, BTreeSet<&'a str>> {
+    let mut font_families_by_key: BTreeMap<String, BTreeSet<&'a str>> = BTreeMap::new();
+    for (metrics_key, font_family) in font_families {
+        font_families_by_key
+            .entry(metrics_key)
+            .or_default()
+            .insert(font_family);
+    }
+    font_families_by_key.retain(|_, font_families| font_families.len() > 1);
+    font_families_by_key
+}
+
+/// Run once the fonts of a module graph are resolved, see
+/// [[check_next_fonts]], with each font family asking for strict fallbacks
+/// whose fallback is [[FontFallback::Error]]. Returns a single error listing
+/// them all, failing the build.
+pub(crate) fn check_font_fallbacks(failed_font_families: &[&str]) -> Option<NextFontsIssue> {
+    let Err(error) = consolidate_fallback_errors(failed_font_families) else {
+        return None
+    };
+
+    Some(NextFontsIssue {
+        severity: IssueSeverity::Error,
+        title: error.to_string(),
+        description: "These fonts require a fallback font with `strict_fallbacks`. See the other \
+                      issues of each font for why its fallback couldn't be generated."
+            .to_owned(),
+    })
+}
+
+fn consolidate_fallback_errors(failed_font_families: &[&str]) -> Result<()> {
+    if failed_font_families.is_empty() {
+        return Ok(());
+    }
+
+    bail!(
+        "Failed to generate fallback fonts for {} font(s): {}",
+        failed_font_families.len(),
+        failed_font_families
+            .iter()
+            .map(|font_family| format!("`{}`", font_family))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn find_duplicates(values: impl IntoIterator<Item = String>) -> BTreeSet<String> {
+    let mut seen = HashSet::new();
+    values
+        .into_iter()
+        .filter(|value| !seen.insert(value.clone()))
+        .collect()
+}
+
 /// An adjustment to be made to a fallback font to approximate the geometry of
 /// the main webfont. Rendered as e.g. `ascent-override: 56.8%;` in the
 /// stylesheet
-#[derive(Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 pub(crate) struct FontAdjustment {
     pub ascent: f64,
     pub descent: f64,
@@ -68,3 +317,425 @@ pub(crate) struct FontAdjustment {
 // Necessary since floating points in this struct don't implement Eq, but it's
 // required for turbo tasks values.
 impl Eq for FontAdjustment {}
+
+/// How closely an adjusted fallback font approximates the webfont, from best
+/// (`A`) to worst (`C`).
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TraceRawVcs,
+)]
+pub(crate) enum FallbackMatchQuality {
+    A,
+    B,
+    C,
+}
+
+impl FallbackMatchQuality {
+    /// A rough estimate of the share of the layout shift caused by swapping the
+    /// fallback for the webfont that a match of this quality avoids.
+    pub(crate) fn estimated_cls_improvement(&self) -> f64 {
+        match self {
+            FallbackMatchQuality::A => 0.9,
+            FallbackMatchQuality::B => 0.6,
+            FallbackMatchQuality::C => 0.3,
+        }
+    }
+}
+
+/// Combines the match quality of every font in a project into a single
+/// estimate of the share of font-swap layout shift avoided, for reporting.
+/// Each font is weighted by how much it is used, e.g. by the number of pages
+/// using it. Returns `None` when there are no fonts or all weights are zero.
+pub(crate) fn estimate_project_cls_improvement(
+    fonts: &[(FallbackMatchQuality, f64)],
+) -> Option<f64> {
+    let total_weight: f64 = fonts.iter().map(|(_, weight)| weight).sum();
+    if total_weight <= 0.0 {
+        return None;
+    }
+
+    let weighted_improvement: f64 = fonts
+        .iter()
+        .map(|(quality, weight)| quality.estimated_cls_improvement() * weight)
+        .sum();
+    Some(weighted_improvement / total_weight)
+}
+
+impl FontAdjustment {
+    /// The overrides and `size_adjust` as unrounded percentage numbers, e.g.
+    /// `93.24...` for `ascent-override: 93.24%;`, in the order `(ascent,
+    /// descent, line_gap, size_adjust)`. Descent is positive, as in CSS.
+    pub(crate) fn override_percentages(&self) -> (f64, f64, f64, f64) {
+        (
+            self.ascent * 100.0,
+            self.descent.abs() * 100.0,
+            self.line_gap * 100.0,
+            self.size_adjust * 100.0,
+        )
+    }
+
+    /// The height of the line box described by the overrides, as a fraction of
+    /// the font size before size-adjust is applied: `ascent + |descent| +
+    /// line_gap`.
+    pub(crate) fn line_box_sum(&self) -> f64 {
+        self.ascent + self.descent.abs() + self.line_gap
+    }
+
+    /// Multiplies `size_adjust` by `scale`. Overrides are scaled by size-adjust
+    /// when rendered, so they are divided by `scale` to keep the line box as
+    /// tall as before.
+    pub(crate) fn with_scaled_size_adjust(&self, scale: f64) -> FontAdjustment {
+        FontAdjustment {
+            ascent: self.ascent / scale,
+            descent: self.descent / scale,
+            line_gap: self.line_gap / scale,
+            size_adjust: self.size_adjust * scale,
+        }
+    }
+
+    /// Redistributes the line box above and below the baseline so that the
+    /// descent is `ratio` times the ascent, keeping the height of the line box
+    /// and the sign of the descent.
+    pub(crate) fn with_descent_ascent_ratio(&self, ratio: f64) -> FontAdjustment {
+        let height = self.ascent + self.descent.abs();
+        let ascent = height / (1.0 + ratio);
+
+        FontAdjustment {
+            ascent,
+            descent: (height - ascent).copysign(self.descent),
+            line_gap: self.line_gap,
+            size_adjust: self.size_adjust,
+        }
+    }
+
+    /// Rounds `size_adjust` to the nearest multiple of `step`, e.g. `0.005` for
+    /// half a percent, rescaling the vertical overrides like
+    /// [[FontAdjustment::with_scaled_size_adjust]] so the line box keeps its
+    /// height.
+    pub(crate) fn with_quantized_size_adjust(&self, step: f64) -> FontAdjustment {
+        let quantized = (self.size_adjust / step).round() * step;
+        if quantized <= 0.0 {
+            return self.clone();
+        }
+
+        self.with_scaled_size_adjust(quantized / self.size_adjust)
+    }
+
+    /// Rounds the vertical overrides so that they render as whole pixels at
+    /// `font_size` pixels, keeping `size_adjust` as is. Overrides are scaled by
+    /// size-adjust when rendered.
+    pub(crate) fn rounded_to_pixels(&self, font_size: f64) -> FontAdjustment {
+        self.rounded_to_steps(font_size, 1.0)
+    }
+
+    /// Like [[FontAdjustment::rounded_to_pixels]], but rounds to multiples of
+    /// `step` pixels, e.g. `1.0 / 64.0` for Safari's layout precision.
+    pub(crate) fn rounded_to_steps(&self, font_size: f64, step: f64) -> FontAdjustment {
+        let scale = self.size_adjust * font_size / step;
+        let round = |value: f64| (value * scale).round() / scale;
+
+        FontAdjustment {
+            ascent: round(self.ascent),
+            descent: round(self.descent),
+            line_gap: round(self.line_gap),
+            size_adjust: self.size_adjust,
+        }
+    }
+
+    /// Rounds every value as it is rendered in CSS, i.e. as a percentage with
+    /// two decimals.
+    pub(crate) fn rounded_for_css(&self) -> FontAdjustment {
+        let round = |value: f64| (value * 10000.0).round() / 10000.0;
+
+        FontAdjustment {
+            ascent: round(self.ascent),
+            descent: round(self.descent),
+            line_gap: round(self.line_gap),
+            size_adjust: round(self.size_adjust),
+        }
+    }
+
+    /// Whether applying this adjustment to `fallback_font` would change its
+    /// rendering by no more than `epsilon` in any of its metrics.
+    pub(crate) fn is_identity(&self, fallback_font: &DefaultFallbackFont, epsilon: f64) -> bool {
+        let units_per_em = fallback_font.units_per_em as f64;
+
+        (self.size_adjust - 1.0).abs() <= epsilon
+            && (self.ascent - fallback_font.ascent as f64 / units_per_em).abs() <= epsilon
+            && (self.descent - fallback_font.descent as f64 / units_per_em).abs() <= epsilon
+            && (self.line_gap - fallback_font.line_gap as f64 / units_per_em).abs() <= epsilon
+    }
+
+    /// Grades the match between the webfont and `fallback_font` by the larger
+    /// of two relative deviations: how far `size_adjust` is from 100%, and how
+    /// much the overrides change the height of the fallback's line box. Small
+    /// deviations mean the fallback already resembles the webfont closely.
+    pub(crate) fn match_quality(
+        &self,
+        fallback_font: &DefaultFallbackFont,
+    ) -> FallbackMatchQuality {
+        let size_deviation = (self.size_adjust - 1.0).abs();
+
+        let fallback_line_box = (fallback_font.ascent as f64
+            + (fallback_font.descent as f64).abs()
+            + fallback_font.line_gap as f64)
+            / fallback_font.units_per_em as f64;
+        // Overrides are scaled by size-adjust when rendered
+        let adjusted_line_box = self.line_box_sum() * self.size_adjust;
+        let vertical_deviation = (adjusted_line_box - fallback_line_box).abs() / fallback_line_box;
+
+        let deviation = size_deviation.max(vertical_deviation);
+        if deviation <= 0.05 {
+            FallbackMatchQuality::A
+        } else if deviation <= 0.15 {
+            FallbackMatchQuality::B
+        } else {
+            FallbackMatchQuality::C
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::collections::BTreeSet;
+
+    use anyhow::Result;
+
+    use super::{
+        consolidate_fallback_errors, default_fallback_fonts, estimate_project_cls_improvement,
+        find_scoped_font_family_collisions, hash_font_fallback_content, FallbackMatchQuality,
+        FontAdjustment, FontFallbackContent, DEFAULT_SANS_SERIF_FONT,
+    };
+    use crate::next_font::util::{format_scoped_font_family, FontFamilyType};
+
+    /// The adjustment of Inter's metrics for Arial, shared by the next/font
+    /// tests
+    pub(crate) const INTER_ADJUSTMENT: FontAdjustment = FontAdjustment {
+        ascent: 0.9324334770490376,
+        descent: -0.23242476700635833,
+        line_gap: 0.0,
+        size_adjust: 1.0389481114147647,
+    };
+
+    #[test]
+    fn test_lists_default_fallback_fonts_with_metrics() {
+        let metrics = |name: &str| {
+            default_fallback_fonts()
+                .into_iter()
+                .find(|font| font.name == name)
+                .map(|font| {
+                    (
+                        font.generic_family.as_str(),
+                        font.ascent,
+                        font.descent,
+                        font.line_gap,
+                        font.units_per_em,
+                    )
+                })
+        };
+
+        assert_eq!(metrics("Arial"), Some(("sans-serif", 1854, -434, 67, 2048)));
+        assert_eq!(
+            metrics("Times New Roman"),
+            Some(("serif", 1825, -443, 87, 2048))
+        );
+        assert_eq!(metrics("Helvetica"), None);
+    }
+
+    #[test]
+    fn test_default_fallback_fonts_are_valid() {
+        for font in default_fallback_fonts() {
+            assert!(
+                font.units_per_em > 0,
+                "{} must have a positive units_per_em",
+                font.name
+            );
+            assert!(
+                font.x_width_avg.is_finite() && font.x_width_avg > 0.0,
+                "{} must have a nonzero x_width_avg",
+                font.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_match_quality_of_near_perfect_match() {
+        // Arial's own metrics, expressed as an adjustment
+        let adjustment = FontAdjustment {
+            ascent: 1854.0 / 2048.0,
+            descent: -434.0 / 2048.0,
+            line_gap: 67.0 / 2048.0,
+            size_adjust: 1.0,
+        };
+
+        assert_eq!(
+            adjustment.match_quality(&DEFAULT_SANS_SERIF_FONT),
+            FallbackMatchQuality::A
+        );
+    }
+
+    #[test]
+    fn test_match_quality_of_poor_match() {
+        let adjustment = FontAdjustment {
+            ascent: 0.8,
+            descent: -0.2,
+            line_gap: 0.0,
+            size_adjust: 1.3,
+        };
+
+        assert_eq!(
+            adjustment.match_quality(&DEFAULT_SANS_SERIF_FONT),
+            FallbackMatchQuality::C
+        );
+    }
+
+    #[test]
+    fn test_rounds_adjustment_to_pixels() {
+        let adjustment = INTER_ADJUSTMENT;
+
+        for font_size in [16.0, 14.0] {
+            let rounded = adjustment.rounded_to_pixels(font_size);
+            assert_eq!(rounded.size_adjust, adjustment.size_adjust);
+
+            let scale = rounded.size_adjust * font_size;
+            for (value, original) in [
+                (rounded.ascent, adjustment.ascent),
+                (rounded.descent, adjustment.descent),
+                (rounded.line_gap, adjustment.line_gap),
+            ] {
+                let pixels = value * scale;
+                assert!((pixels - pixels.round()).abs() < 1e-9);
+                assert!((pixels - original * scale).abs() <= 0.5 + 1e-9);
+            }
+        }
+
+        let rounded = adjustment.rounded_to_pixels(16.0);
+        assert_eq!((rounded.descent * rounded.size_adjust * 16.0).round(), -4.0);
+        let rounded = adjustment.rounded_to_pixels(14.0);
+        assert_eq!((rounded.ascent * rounded.size_adjust * 14.0).round(), 14.0);
+    }
+
+    #[test]
+    fn test_scales_size_adjust() {
+        let adjustment = INTER_ADJUSTMENT;
+
+        let scaled = adjustment.with_scaled_size_adjust(1.02);
+        assert_eq!(scaled.size_adjust, 1.0389481114147647 * 1.02);
+        assert!(
+            (scaled.ascent * scaled.size_adjust - adjustment.ascent * adjustment.size_adjust).abs()
+                < 1e-12
+        );
+    }
+
+    #[test]
+    fn test_line_box_sum() {
+        // The adjustment for Inter against Arial
+        let adjustment = INTER_ADJUSTMENT;
+
+        assert_eq!(adjustment.line_box_sum(), 1.1648582440553958);
+    }
+
+    #[test]
+    fn test_finds_scoped_font_family_collisions() {
+        let inter = format_scoped_font_family(&FontFamilyType::Fallback, "Inter", 0xc123b8);
+        // A different request whose hash shares the same first six digits
+        let colliding_inter =
+            format_scoped_font_family(&FontFamilyType::Fallback, "Inter", 0xc123b8ff);
+        let roboto = format_scoped_font_family(&FontFamilyType::Fallback, "Roboto", 0xc123b8);
+
+        assert_eq!(
+            find_scoped_font_family_collisions([
+                (inter.as_str(), 0xc123b8),
+                // The same request, e.g. from its CSS module
+                (inter.as_str(), 0xc123b8),
+                (roboto.as_str(), 0xc123b8),
+                (colliding_inter.as_str(), 0xc123b8ff),
+            ]),
+            BTreeSet::from(["__Inter_Fallback_c123b8".to_owned()])
+        );
+        assert!(find_scoped_font_family_collisions([
+            (inter.as_str(), 0xc123b8),
+            (inter.as_str(), 0xc123b8)
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn test_estimates_project_cls_improvement() {
+        let estimate = estimate_project_cls_improvement(&[
+            (FallbackMatchQuality::A, 3.0),
+            (FallbackMatchQuality::C, 1.0),
+        ])
+        .unwrap();
+        assert!((estimate - (0.9 * 3.0 + 0.3) / 4.0).abs() < 1e-12);
+
+        assert_eq!(estimate_project_cls_improvement(&[]), None);
+    }
+
+    #[test]
+    fn test_consolidates_fallback_errors() {
+        assert!(consolidate_fallback_errors(&[]).is_ok());
+
+        let error = consolidate_fallback_errors(&["Foo", "Bar Sans"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to generate fallback fonts for 2 font(s): `Foo`, `Bar Sans`"
+        );
+    }
+
+    #[test]
+    fn test_unadjusted_fallback_is_identity() {
+        for font in default_fallback_fonts() {
+            assert!(font.unadjusted().is_identity(font, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_content_hash_changes_with_adjustment() -> Result<()> {
+        let content = |size_adjust| FontFallbackContent::Automatic {
+            scoped_font_family: format_scoped_font_family(
+                &FontFamilyType::Fallback,
+                "Inter",
+                0xc123b8,
+            ),
+            local_font_family: "Arial".to_owned(),
+            adjustment: Some(FontAdjustment {
+                size_adjust,
+                ..INTER_ADJUSTMENT
+            }),
+            css_byte_budget: None,
+            size_adjust_supports_guard: false,
+            font_weight_range: None,
+            variable: None,
+            named_instance: None,
+            css_format: None,
+            debug_custom_properties: false,
+            debug_unadjusted_overrides: false,
+            font_stack: vec![],
+        };
+
+        assert_eq!(
+            hash_font_fallback_content(&content(1.0389481114147647))?,
+            hash_font_fallback_content(&content(1.0389481114147647))?
+        );
+        assert_ne!(
+            hash_font_fallback_content(&content(1.0389481114147647))?,
+            hash_font_fallback_content(&content(1.04))?
+        );
+        assert_ne!(
+            hash_font_fallback_content(&FontFallbackContent::Error)?,
+            hash_font_fallback_content(&FontFallbackContent::Skipped)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_override_percentages() {
+        let adjustment = INTER_ADJUSTMENT;
+
+        let (ascent, descent, line_gap, size_adjust) = adjustment.override_percentages();
+        assert!((ascent - 93.24334770490376).abs() < 1e-9);
+        assert!((descent - 23.242476700635833).abs() < 1e-9);
+        assert_eq!(line_gap, 0.0);
+        assert!((size_adjust - 103.89481114147647).abs() < 1e-9);
+    }
+}
@@ -1,53 +1,527 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    future::Future,
+    ops::{Deref, RangeInclusive},
+    time::{Duration, Instant},
+};
 
-use anyhow::{Context, Result};
-use once_cell::sync::Lazy;
-use regex::Regex;
-use serde::{Deserialize, Serialize};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{
+    de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
 use turbo_binding::{turbo::tasks_fs::FileSystemPathVc, turbopack::core::issue::IssueSeverity};
 use turbo_tasks::{
     primitives::{StringVc, StringsVc, U32Vc},
     trace::TraceRawVcs,
+    TryJoinIterExt,
 };
 
-use super::options::NextFontGoogleOptionsVc;
+use super::options::{
+    FallbackPreset, FamilyIdIndex, FontAdjustmentOverride, FontMetricsOverride, FontWeights,
+    NextFontGoogleOptions, NextFontGoogleOptionsVc, SelfHostedFontMetrics,
+};
 use crate::{
     next_font::{
         font_fallback::{
-            AutomaticFontFallback, FontAdjustment, FontFallback, FontFallbackVc,
-            DEFAULT_SANS_SERIF_FONT, DEFAULT_SERIF_FONT,
+            default_fallback_fonts, find_default_fallback_font, AutomaticFontFallback,
+            DefaultFallbackFont, FallbackMatchQuality, FontAdjustment, FontFallback,
+            FontFallbackVc, DEFAULT_SANS_SERIF_FONT, DEFAULT_SERIF_FONT,
+        },
+        issue::{IssueCause, NextFontIssue, NextFontUsage},
+        stylesheet::build_override_properties,
+        util::{
+            get_scoped_font_family, get_versioned_request_hash, quote_font_family,
+            FontFamilyQuoting, FontFamilyType,
         },
-        issue::NextFontIssue,
-        util::{get_scoped_font_family, FontFamilyType},
     },
-    util::load_next_json,
+    util::{load_next_file, load_next_json},
 };
 
 /// An entry in the Google fonts metrics map
-#[derive(Deserialize)]
+#[turbo_tasks::value(shared, eq = "manual")]
+#[derive(Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub(super) struct FontMetricsMapEntry {
+    pub family_name: String,
+    /// Treated as `sans-serif` when missing
+    #[serde(default)]
+    pub category: Option<FontCategory>,
     #[allow(unused)]
-    family_name: String,
-    category: String,
-    #[allow(unused)]
-    cap_height: i32,
-    ascent: i32,
-    descent: i32,
-    line_gap: u32,
-    units_per_em: u32,
-    #[allow(unused)]
-    x_height: i32,
-    x_width_avg: f64,
+    pub cap_height: i32,
+    pub ascent: i32,
+    pub descent: i32,
+    pub line_gap: u32,
+    pub units_per_em: u32,
+    pub x_height: i32,
+    pub x_width_avg: f64,
+    /// Metrics that differ for a style of the font, keyed by style, e.g.
+    /// `italic`
+    #[serde(default)]
+    pub styles: BTreeMap<String, FontStyleMetrics>,
+    /// Metrics at values of the optical size (`opsz`) axis of variable fonts
+    /// other than the default, keyed by optical size, e.g. `72`
+    #[serde(default)]
+    pub optical_sizes: BTreeMap<u16, FontOpticalSizeMetrics>,
+    /// The optical size the metrics above were measured at for variable
+    /// fonts with an optical size axis, e.g. `32`
+    #[serde(default)]
+    pub default_optical_size: Option<u16>,
+    /// The system font to use as the fallback instead of the default for the
+    /// font's category, e.g. `Arial`. Only honored for default fallback fonts,
+    /// as there are no metrics to adjust other fonts with.
+    #[serde(default)]
+    pub preferred_fallback: Option<String>,
+    /// The version of the font the metrics were measured from, e.g. `3.019`
+    #[serde(default)]
+    pub version: Option<String>,
+    /// The font's Google Fonts family ID, to look the font up by with
+    /// `NextFontGoogleOptions::family_id`
+    #[serde(default)]
+    pub family_id: Option<String>,
 }
 
-#[derive(Deserialize)]
+/// Metrics of a font that differ between its styles
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct FontStyleMetrics {
+    pub x_width_avg: f64,
+}
+
+impl FontMetricsMapEntry {
+    /// Returns these metrics as they apply to `style`, e.g. with the average
+    /// width of the italic face for `italic`.
+    fn for_style(&self, style: Option<&str>) -> FontMetricsMapEntry {
+        let mut metrics = self.clone();
+        if let Some(style_metrics) = style.and_then(|style| self.styles.get(style)) {
+            metrics.x_width_avg = style_metrics.x_width_avg;
+        }
+        metrics
+    }
+
+    /// Returns these metrics at the optical size nearest to `optical_size`
+    /// that has metrics, including the default optical size if it is known.
+    /// Without metrics for other optical sizes, the metrics at the default
+    /// optical size are returned as is.
+    fn for_optical_size(&self, optical_size: Option<f64>) -> FontMetricsMapEntry {
+        let mut metrics = self.clone();
+        let Some(optical_size) = optical_size else {
+            return metrics;
+        };
+        let distance = |candidate: u16| (f64::from(candidate) - optical_size).abs();
+        let nearest = self
+            .optical_sizes
+            .iter()
+            .min_by(|(a, _), (b, _)| distance(**a).total_cmp(&distance(**b)));
+        match nearest {
+            Some((nearest, _))
+                if self
+                    .default_optical_size
+                    .map_or(false, |default| distance(default) <= distance(*nearest)) => {}
+            Some((_, optical_size_metrics)) => {
+                metrics.ascent = optical_size_metrics.ascent;
+                metrics.descent = optical_size_metrics.descent;
+                metrics.line_gap = optical_size_metrics.line_gap;
+                metrics.x_width_avg = optical_size_metrics.x_width_avg;
+            }
+            None => {}
+        }
+        metrics
+    }
+}
+
+/// Metrics of a variable font at an optical size, see
+/// [[FontMetricsMapEntry::optical_sizes]]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct FontOpticalSizeMetrics {
+    pub ascent: i32,
+    pub descent: i32,
+    pub line_gap: u32,
+    pub x_width_avg: f64,
+}
+
+/// The category of a font in the metrics map, e.g. `serif`. May list several
+/// categories, either comma-separated or as an array.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub(super) enum FontCategory {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl FontCategory {
+    pub fn categories(&self) -> Vec<&str> {
+        match self {
+            FontCategory::One(categories) => categories.split(',').map(str::trim).collect(),
+            FontCategory::Many(categories) => categories.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// Whether any of the categories is emoji, for color fonts that shouldn't
+    /// get a text fallback
+    pub fn is_emoji(&self) -> bool {
+        self.categories().contains(&"emoji")
+    }
+
+    /// Whether any of the categories is serif, e.g. for a serif display font
+    pub fn is_serif(&self) -> bool {
+        self.categories().contains(&"serif")
+    }
+
+    /// Whether the category is given but names no category, e.g. `""`, as
+    /// opposed to a missing category
+    pub fn is_empty(&self) -> bool {
+        self.categories().iter().all(|category| category.is_empty())
+    }
+}
+
+#[turbo_tasks::value(shared, eq = "manual")]
+#[derive(Clone, Debug, PartialEq)]
 pub(super) struct FontMetricsMap(pub HashMap<String, FontMetricsMapEntry>);
 
+#[turbo_tasks::value(transparent)]
+pub(super) struct OptionFontMetricsMapEntry(Option<FontMetricsMapEntryVc>);
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 struct Fallback {
     pub font_family: String,
     pub adjustment: Option<FontAdjustment>,
+    /// The full-precision `adjustment` when it was rounded for CSS, see
+    /// [[FallbackLookupOptions::keep_precise_adjustment]]
+    pub precise_adjustment: Option<FontAdjustment>,
+    /// Problems found while looking up the fallback that didn't prevent
+    /// generating it, to be emitted as issues.
+    pub warnings: Vec<FallbackWarning>,
+    /// The version of the font the fallback was computed for, see
+    /// [[FontMetricsMapEntry::version]]
+    pub font_version: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+struct FallbackWarning {
+    pub title: String,
+    pub description: String,
+}
+
+/// The raw values a preview UI needs to render a webfont's automatic fallback,
+/// as numbers rather than CSS. Override values are fractions of the font size.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FallbackPreview {
+    pub scoped_font_family: String,
+    pub local_font_family: String,
+    pub adjustment: Option<FallbackPreviewAdjustment>,
+    /// See [[FontFallbackVc::content_hash]], in hexadecimal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Every default fallback font considered, see [[get_fallback_candidates]]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<FallbackCandidate>,
+    /// The candidate needing the smallest `size_adjust`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nearest_candidate: Option<String>,
+    /// What enabling adjustment would add when it's disabled, see
+    /// [[adjustment_impact]]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adjustment_impact: Option<FallbackPreviewAdjustment>,
+    /// How the fallback was decided, see [[get_fallback_decision_tree]]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision_tree: Option<FallbackDecisionNode>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FallbackPreviewAdjustment {
+    pub ascent: f64,
+    pub descent: f64,
+    pub line_gap: f64,
+    pub size_adjust: f64,
+    pub line_box_sum: f64,
+    /// See [[FontAdjustment::match_quality]]. `None` if the fallback isn't one
+    /// of the default fallback fonts.
+    pub match_quality: Option<FallbackMatchQuality>,
+}
+
+impl FallbackPreviewAdjustment {
+    fn new(adjustment: &FontAdjustment, local_font_family: &str) -> Self {
+        FallbackPreviewAdjustment {
+            ascent: adjustment.ascent,
+            descent: adjustment.descent,
+            line_gap: adjustment.line_gap,
+            size_adjust: adjustment.size_adjust,
+            line_box_sum: adjustment.line_box_sum(),
+            match_quality: find_default_fallback_font(local_font_family)
+                .map(|fallback_font| adjustment.match_quality(fallback_font)),
+        }
+    }
+}
+
+impl FallbackPreview {
+    fn new(scoped_font_family: String, fallback: &Fallback) -> Self {
+        FallbackPreview {
+            scoped_font_family,
+            local_font_family: fallback.font_family.clone(),
+            adjustment: fallback.adjustment.as_ref().map(|adjustment| {
+                FallbackPreviewAdjustment::new(adjustment, &fallback.font_family)
+            }),
+            content_hash: None,
+            candidates: vec![],
+            nearest_candidate: None,
+            adjustment_impact: None,
+            decision_tree: None,
+        }
+    }
+
+    /// Adds what explains the fallback of the font of `options` to the
+    /// preview, from the metrics the fallback was resolved with. Left out for
+    /// fonts without bundled metrics, e.g. self-hosted ones.
+    fn with_explanation(
+        mut self,
+        options: &NextFontGoogleOptions,
+        font_metrics_map: &FontMetricsMap,
+    ) -> Self {
+        if let Ok((candidates, nearest)) =
+            get_fallback_candidates(&options.font_family, font_metrics_map)
+        {
+            self.nearest_candidate = Some(candidates[nearest].font_family.clone());
+            self.candidates = candidates;
+        }
+        let lookup_options = FallbackLookupOptions::from(options);
+        if !lookup_options.adjust {
+            if let Ok(Some(adjustment)) = adjustment_impact(&options.font_family, font_metrics_map)
+            {
+                self.adjustment_impact = Some(FallbackPreviewAdjustment::new(
+                    &adjustment,
+                    &self.local_font_family,
+                ));
+            }
+        }
+        self.decision_tree =
+            get_fallback_decision_tree(&options.font_family, font_metrics_map, &lookup_options)
+                .ok();
+        self
+    }
+}
+
+/// See [[FontAdjustment::match_quality]]. `None` if the fallback isn't adjusted
+/// or isn't one of the default fallback fonts.
+fn fallback_match_quality(fallback: &Fallback) -> Option<FallbackMatchQuality> {
+    let adjustment = fallback.adjustment.as_ref()?;
+    find_default_fallback_font(&fallback.font_family)
+        .map(|fallback_font| adjustment.match_quality(fallback_font))
+}
+
+/// Safari (WebKit) lays out text in fixed-point units of 1/64 pixel
+const SAFARI_LAYOUT_UNIT: f64 = 1.0 / 64.0;
+
+/// The font size Safari quantization is computed for when no
+/// `fallback_font_size` is given, the default font size of browsers
+const DEFAULT_FONT_SIZE: f64 = 16.0;
+
+/// The range of `unitsPerEm` the OpenType specification allows. Metrics with
+/// units per em outside of it, e.g. close to `u32::MAX`, are treated as
+/// invalid rather than computed with.
+const VALID_UNITS_PER_EM: RangeInclusive<u32> = 16..=16384;
+
+/// Adjustments within this distance of the fallback font's own metrics are
+/// invisible once rendered as percentages with two decimals, and are skipped.
+const DEFAULT_ADJUSTMENT_EPSILON: f64 = 0.00005;
+
+/// Controls how [[lookup_fallback]] derives a fallback from a font's metrics.
+#[derive(Debug)]
+struct FallbackLookupOptions {
+    /// Whether to compute a [[FontAdjustment]] at all
+    pub adjust: bool,
+    /// The distance from the fallback font's own metrics under which an
+    /// adjustment is considered negligible and omitted
+    pub adjustment_epsilon: f64,
+    /// Whether to name only the generic family of the fallback font, e.g.
+    /// `sans-serif`. The adjustment is still computed against the metrics of
+    /// the font typically used for it.
+    pub generic_only: bool,
+    /// The font size in pixels to round vertical overrides for, if any
+    pub font_size: Option<f64>,
+    /// Experimental: a factor to apply to `size_adjust` for high-DPI displays
+    pub dpi_scale: Option<f64>,
+    /// Whether to override the line gap to zero rather than match the webfont
+    pub zero_line_gap: bool,
+    /// Whether to round the adjustment as rendered in CSS, keeping the
+    /// full-precision adjustment as well
+    pub keep_precise_adjustment: bool,
+    /// Whether to round overrides to Safari's layout precision, see
+    /// [[SAFARI_LAYOUT_UNIT]]
+    pub safari_quantization: bool,
+    /// The requested style, e.g. `italic`, to use style-specific metrics for
+    pub style: Option<String>,
+    /// The `size_adjust` above which to warn about a poorly matching fallback
+    pub size_adjust_warning_threshold: Option<f64>,
+    /// Whether to declare the fallback as its local font family rather than a
+    /// scoped font family
+    pub unscoped_font_family: bool,
+    /// Values replacing fields of the computed adjustment
+    pub adjustment_override: Option<FontAdjustmentOverride>,
+    /// How much `size_adjust` matches the x-height rather than the width
+    pub x_height_weight: f64,
+    /// The step in percentage points to round `size_adjust` to, e.g. `0.5`
+    pub size_adjust_step: Option<f64>,
+    /// The ratio of the descent to the ascent override to enforce, if any
+    pub descent_ascent_ratio: Option<f64>,
+    /// The optical size the font is used at, to use the metrics at the nearest
+    /// optical size of variable fonts with an `opsz` axis
+    pub optical_size: Option<f64>,
+    /// The default fallback font to adjust against, e.g. `Arial`, instead of
+    /// the one for the font's category
+    pub fallback_font: Option<&'static str>,
+}
+
+impl Default for FallbackLookupOptions {
+    fn default() -> Self {
+        FallbackLookupOptions {
+            adjust: true,
+            adjustment_epsilon: DEFAULT_ADJUSTMENT_EPSILON,
+            generic_only: false,
+            font_size: None,
+            dpi_scale: None,
+            zero_line_gap: false,
+            keep_precise_adjustment: false,
+            safari_quantization: false,
+            style: None,
+            size_adjust_warning_threshold: None,
+            unscoped_font_family: false,
+            adjustment_override: None,
+            x_height_weight: 0.0,
+            size_adjust_step: None,
+            descent_ascent_ratio: None,
+            optical_size: None,
+            fallback_font: None,
+        }
+    }
+}
+
+impl From<&NextFontGoogleOptions> for FallbackLookupOptions {
+    fn from(options: &NextFontGoogleOptions) -> Self {
+        FallbackLookupOptions {
+            adjust: options.adjust_font_fallback,
+            adjustment_epsilon: options
+                .adjustment_epsilon
+                .map_or(DEFAULT_ADJUSTMENT_EPSILON, |epsilon| epsilon.0),
+            generic_only: options.fallback_generic_only,
+            font_size: options.fallback_font_size.map(f64::from),
+            dpi_scale: options.experimental_fallback_dpi_scale.map(|scale| scale.0),
+            zero_line_gap: options.zero_fallback_line_gap,
+            keep_precise_adjustment: options.keep_precise_fallback_adjustment,
+            safari_quantization: options.safari_fallback_quantization,
+            style: options.styles.first().cloned(),
+            size_adjust_warning_threshold: options
+                .size_adjust_warning_threshold
+                .map(|threshold| threshold.0),
+            unscoped_font_family: options.unscoped_fallback_font_family,
+            adjustment_override: options.fallback_adjustment_override.clone(),
+            x_height_weight: options
+                .fallback_x_height_weight
+                .map_or(0.0, |weight| weight.0.clamp(0.0, 1.0)),
+            size_adjust_step: options
+                .fallback_size_adjust_step
+                .map(|step| step.0)
+                .filter(|step| *step > 0.0),
+            descent_ascent_ratio: options
+                .fallback_descent_ascent_ratio
+                .map(|ratio| ratio.0)
+                .filter(|ratio| *ratio >= 0.0),
+            optical_size: options.fallback_optical_size.map(f64::from),
+            fallback_font: options
+                .fallback_preset
+                .and_then(FallbackPreset::adjusted_font_family),
+        }
+    }
+}
+
+/// Metrics files bundled with Next.js, relative to its package root. Later
+/// files take precedence over earlier ones for fonts present in several.
+static FONT_METRICS_FILES: &[&str] = &["/dist/server/capsize-font-metrics.json"];
+
+/// Fonts installed on every platform we target. Requesting one of these
+/// doesn't need a metrics-adjusted fallback, as the font itself is always
+/// available locally.
+static SYSTEM_FONTS: &[&str] = &[
+    "Arial",
+    "Courier New",
+    "Georgia",
+    "Tahoma",
+    "Times New Roman",
+    "Trebuchet MS",
+    "Verdana",
+];
+
+/// The generic font families of CSS, which are always recognized in a manual
+/// fallback list
+static GENERIC_FONT_FAMILIES: &[&str] = &[
+    "serif",
+    "sans-serif",
+    "monospace",
+    "cursive",
+    "fantasy",
+    "system-ui",
+    "ui-serif",
+    "ui-sans-serif",
+    "ui-monospace",
+    "ui-rounded",
+    "emoji",
+    "math",
+    "fangsong",
+];
+
+/// Prefixes that are sometimes included in font families but aren't part of
+/// the name, e.g. `Google Fonts: Inter`. Longer prefixes come first.
+static REDUNDANT_FONT_FAMILY_PREFIXES: &[&str] = &["Google Fonts", "Google Font", "GF"];
+
+/// Normalizes a requested font family to the name used in the metrics map,
+/// stripping redundant prefixes and Google Fonts API syntax.
+fn normalize_font_family(font_family: &str) -> String {
+    strip_font_api_syntax(strip_redundant_prefixes(font_family.trim()))
+}
+
+/// Strips one of [REDUNDANT_FONT_FAMILY_PREFIXES], ignoring case, when followed
+/// by a separator and another name.
+fn strip_redundant_prefixes(font_family: &str) -> &str {
+    for prefix in REDUNDANT_FONT_FAMILY_PREFIXES {
+        let Some(candidate) = font_family.get(..prefix.len()) else {
+            continue
+        };
+        if !candidate.eq_ignore_ascii_case(prefix) {
+            continue;
+        }
+
+        let rest = &font_family[prefix.len()..];
+        let stripped = rest.trim_start_matches(|c: char| c == ':' || c == '-' || c.is_whitespace());
+        if stripped.len() < rest.len() && !stripped.is_empty() {
+            return stripped;
+        }
+    }
+
+    font_family
+}
+
+/// Strips Google Fonts API syntax from a font family, e.g. `Roboto:wght@400`
+/// becomes `Roboto` and `Open+Sans` becomes `Open Sans`.
+fn strip_font_api_syntax(font_family: &str) -> String {
+    let font_family = match font_family.split_once(':') {
+        Some((font_family, _axes)) => font_family,
+        None => font_family,
+    };
+
+    font_family.replace('+', " ").trim().to_owned()
+}
+
+/// Whether `font_family` is one of the known [SYSTEM_FONTS], ignoring case.
+fn is_system_font(font_family: &str) -> bool {
+    SYSTEM_FONTS
+        .iter()
+        .any(|system_font| system_font.eq_ignore_ascii_case(font_family.trim()))
 }
 
 #[turbo_tasks::function]
@@ -57,201 +531,4248 @@ pub(super) async fn get_font_fallback(
     request_hash: U32Vc,
 ) -> Result<FontFallbackVc> {
     let options = options_vc.await?;
-    Ok(match &options.fallback {
-        Some(fallback) => FontFallback::Manual(StringsVc::cell(fallback.clone())).cell(),
-        None => {
-            let metrics_json =
-                load_next_json(context, "/dist/server/capsize-font-metrics.json").await;
-            match metrics_json {
-                Ok(metrics_json) => {
-                    let fallback = lookup_fallback(
-                        &options.font_family,
-                        metrics_json,
-                        options.adjust_font_fallback,
-                    );
+    let metrics_source = FileMetricsSource {
+        context,
+        options_vc,
+    };
+    let (resolution, duration) = timed(resolve_font_fallback(
+        &metrics_source,
+        &options,
+        &*options_vc.quoting().await?,
+    ))
+    .await;
+    let resolution = resolution?;
+    let mut grouped_causes = vec![];
+    for issue in resolution.issues {
+        if let (true, Some(cause)) = (options.group_fallback_issues, issue.cause) {
+            grouped_causes.push(cause);
+            continue;
+        }
+        NextFontIssue {
+            path: context,
+            title: StringVc::cell(issue.title),
+            description: StringVc::cell(issue.description),
+            severity: issue.severity.cell(),
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
 
-                    match fallback {
-                        Ok(fallback) => FontFallback::Automatic(
-                            AutomaticFontFallback {
-                                scoped_font_family: get_scoped_font_family(
-                                    FontFamilyType::Fallback.cell(),
-                                    options_vc.font_family(),
-                                    request_hash,
-                                ),
-                                local_font_family: StringVc::cell(fallback.font_family),
-                                adjustment: fallback.adjustment,
-                            }
-                            .cell(),
-                        )
-                        .cell(),
-                        Err(_) => {
-                            NextFontIssue {
-                                path: context,
-                                title: StringVc::cell(format!(
-                                    "Failed to find font override values for font `{}`",
-                                    &options.font_family,
-                                )),
-                                description: StringVc::cell(
-                                    "Skipping generating a fallback font.".to_owned(),
-                                ),
-                                severity: IssueSeverity::Warning.cell(),
-                            }
-                            .cell()
-                            .as_issue()
-                            .emit();
-                            FontFallback::Error.cell()
-                        }
-                    }
+    let fallback_failed = resolution.fallback == ResolvedFallback::Error;
+    let match_quality = match &resolution.fallback {
+        ResolvedFallback::Automatic(fallback) => fallback_match_quality(fallback),
+        _ => None,
+    };
+
+    let mut preview = None;
+    let fallback = match resolution.fallback {
+        ResolvedFallback::Manual(fallback) => FontFallback::Manual(StringsVc::cell(fallback)),
+        ResolvedFallback::Skipped => FontFallback::Skipped,
+        ResolvedFallback::Error => FontFallback::Error,
+        // A generic family can't be a local font, so it's listed without a
+        // fallback `@font-face`
+        ResolvedFallback::Automatic(fallback) if options.fallback_generic_only => {
+            FontFallback::Manual(StringsVc::cell(vec![fallback.font_family]))
+        }
+        ResolvedFallback::Automatic(fallback) => {
+            let scoped_font_family =
+                match unscoped_font_family(&fallback, &FallbackLookupOptions::from(&*options)) {
+                    Some(font_family) => StringVc::cell(font_family.to_owned()),
+                    None => get_scoped_font_family(
+                        FontFamilyType::Fallback.cell(),
+                        options_vc.font_family(),
+                        match &fallback.font_version {
+                            Some(version) if options.version_fallback_font_family => U32Vc::cell(
+                                get_versioned_request_hash(*request_hash.await?, version),
+                            ),
+                            _ => request_hash,
+                        },
+                    ),
+                };
+            if options.fallback_preview {
+                preview = Some(
+                    FallbackPreview::new(scoped_font_family.await?.clone_value(), &fallback)
+                        .with_explanation(&options, &**metrics_source.load().await?),
+                );
+            }
+
+            FontFallback::Automatic(
+                AutomaticFontFallback {
+                    scoped_font_family,
+                    local_font_family: StringVc::cell(fallback.font_family),
+                    adjustment: fallback.adjustment,
+                    precise_adjustment: fallback.precise_adjustment,
+                    css_byte_budget: options.fallback_css_byte_budget,
+                    size_adjust_supports_guard: options.fallback_size_adjust_supports_guard,
+                    font_weight_range: get_consolidated_weight_range(&options),
+                    variable: options.fallback_variable.clone(),
+                    named_instance: options.fallback_named_instance.clone(),
+                    css_format: options.fallback_css_format,
+                    debug_custom_properties: options.fallback_debug_custom_properties,
+                    debug_unadjusted_overrides: options.fallback_debug_unadjusted_overrides,
+                    // A manual fallback is never automatic, so a preset is in use
+                    font_stack: options
+                        .fallback_preset
+                        .map(preset_font_families)
+                        .unwrap_or_default(),
                 }
-                Err(_) => FontFallback::Error.cell(),
+                .cell(),
+            )
+        }
+    };
+
+    let font_family = normalize_font_family(&options.font_family);
+    NextFontUsage {
+        metrics_key: Some(format_fallback_font_name(&font_family)),
+        font_family,
+        scoped_fallback_font_family: match &fallback {
+            FontFallback::Automatic(automatic) => {
+                Some((*automatic).await?.scoped_font_family.await?.clone_value())
             }
+            _ => None,
+        },
+        request_hash: *request_hash.await?,
+        max_fonts: options.max_fonts,
+        strict_fallbacks: options.strict_fallbacks,
+        fallback_failed,
+        grouped_causes,
+        match_quality,
+        cls_estimate: options.fallback_cls_estimate,
+        fallback_duration: options.fallback_timing.then_some(duration),
+    }
+    .cell()
+    .as_next_font_resolution()
+    .emit();
+
+    let fallback = fallback.cell();
+    if let Some(preview) = preview {
+        let preview = FallbackPreview {
+            content_hash: Some(format!("{:016x}", *fallback.content_hash().await?)),
+            ..preview
+        };
+        NextFontIssue {
+            path: context,
+            title: StringVc::cell(format!(
+                "Fallback preview for font `{}`",
+                options.font_family
+            )),
+            description: StringVc::cell(serde_json::to_string(&preview)?),
+            severity: IssueSeverity::Info.cell(),
         }
-    })
+        .cell()
+        .as_issue()
+        .emit();
+    }
+
+    Ok(fallback)
 }
 
-static FALLBACK_FONT_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^\w|[A-Z]|\b\w)").unwrap());
+/// Awaits `future`, returning its output with how long it took
+async fn timed<T>(future: impl Future<Output = T>) -> (T, Duration) {
+    let started = Instant::now();
+    let output = future.await;
+    (output, started.elapsed())
+}
 
-// From https://github.com/vercel/next.js/blob/1628260b88ce3052ac307a1607b6e8470188ab83/packages/next/src/server/font-utils.ts#L101
-fn format_fallback_font_name(font_family: &str) -> String {
-    let mut fallback_name = FALLBACK_FONT_NAME
-        .replace(font_family, |caps: &regex::Captures| {
-            caps.iter()
-                .enumerate()
-                .map(|(i, font_matches)| {
-                    let font_matches = font_matches.unwrap().as_str();
-                    if i == 0 {
-                        font_matches.to_lowercase()
-                    } else {
-                        font_matches.to_uppercase()
-                    }
-                })
-                .collect::<Vec<String>>()
-                .join("")
-        })
-        .to_string();
-    fallback_name.retain(|c| !c.is_whitespace());
-    fallback_name
+/// A font's fallback as resolved by [[resolve_font_fallback]]
+#[derive(Debug, PartialEq)]
+enum ResolvedFallback {
+    Automatic(Fallback),
+    Manual(Vec<String>),
+    Skipped,
+    Error,
 }
 
-fn lookup_fallback(
-    font_family: &str,
-    font_metrics_map: FontMetricsMap,
-    adjust: bool,
-) -> Result<Fallback> {
-    let font_family = format_fallback_font_name(font_family);
-    let metrics = font_metrics_map
-        .0
-        .get(&font_family)
-        .context("Font not found in metrics")?;
+/// An issue found while resolving a font's fallback, emitted as a
+/// [[NextFontIssue]] by [[get_font_fallback]]
+#[derive(Debug, PartialEq)]
+struct FallbackIssue {
+    pub title: String,
+    pub description: String,
+    pub severity: IssueSeverity,
+    /// The root cause to group the issue by with those of other fonts, if it
+    /// is shared by them, see `NextFontGoogleOptions::group_fallback_issues`
+    pub cause: Option<IssueCause>,
+}
 
-    let fallback = if metrics.category == "serif" {
-        &DEFAULT_SERIF_FONT
-    } else {
-        &DEFAULT_SANS_SERIF_FONT
-    };
+impl From<FallbackWarning> for FallbackIssue {
+    fn from(warning: FallbackWarning) -> Self {
+        FallbackIssue {
+            title: warning.title,
+            description: warning.description,
+            severity: IssueSeverity::Warning,
+            cause: None,
+        }
+    }
+}
 
-    let metrics = if adjust {
-        // Derived from
-        // https://github.com/vercel/next.js/blob/7bfd5829999b1d203e447d30de7e29108c31934a/packages/next/src/server/font-utils.ts#L131
-        let main_font_avg_width = metrics.x_width_avg / metrics.units_per_em as f64;
-        let fallback_font_avg_width = fallback.x_width_avg / fallback.units_per_em as f64;
-        let size_adjust = main_font_avg_width / fallback_font_avg_width;
+#[derive(Debug, PartialEq)]
+struct FallbackResolution {
+    pub fallback: ResolvedFallback,
+    pub issues: Vec<FallbackIssue>,
+}
 
-        let ascent = metrics.ascent as f64 / (metrics.units_per_em as f64 * size_adjust);
-        let descent = metrics.descent as f64 / (metrics.units_per_em as f64 * size_adjust);
-        let line_gap = metrics.line_gap as f64 / (metrics.units_per_em as f64 * size_adjust);
+impl FallbackResolution {
+    fn new(fallback: ResolvedFallback) -> Self {
+        FallbackResolution {
+            fallback,
+            issues: vec![],
+        }
+    }
+}
 
-        Some(FontAdjustment {
-            ascent,
-            descent,
-            line_gap,
-            size_adjust,
-        })
-    } else {
-        None
+/// Resolves the fallback of a font like [[get_font_fallback]], but returns the
+/// issues found along with it rather than emitting them, for consumers other
+/// than the build's issue reporting.
+async fn resolve_font_fallback(
+    metrics_source: &dyn MetricsSource,
+    options: &NextFontGoogleOptions,
+    quoting: &FontFamilyQuoting,
+) -> Result<FallbackResolution> {
+    let font_family = normalize_font_family(&options.font_family);
+    // An empty list of fallback fonts would leave the font without fallback
+    let fallback = options
+        .fallback
+        .as_ref()
+        .filter(|fallback| !fallback.is_empty());
+    let mut resolution = match (fallback, options.fallback_preset) {
+        (Some(fallback), _) => {
+            let mut resolution =
+                FallbackResolution::new(ResolvedFallback::Manual(fallback.clone()));
+            // Metrics failing to load only skip the validation, as the list is
+            // used regardless
+            if let (true, Ok(font_metrics_map)) = (
+                options.validate_manual_fallback,
+                metrics_source.load().await,
+            ) {
+                resolution.issues.extend(
+                    find_unrecognized_fallback_fonts(fallback, &font_metrics_map)
+                        .into_iter()
+                        .map(|unrecognized_font| {
+                            let suggestion = (!options.disable_font_family_suggestions)
+                                .then(|| suggest_font_family(unrecognized_font, &font_metrics_map))
+                                .flatten();
+                            unrecognized_fallback_font_issue(
+                                &options.font_family,
+                                unrecognized_font,
+                                suggestion.as_deref(),
+                            )
+                        }),
+                );
+            }
+            resolution
+        }
+        (None, Some(preset)) if preset.adjusted_font_family().is_none() => {
+            FallbackResolution::new(ResolvedFallback::Manual(preset_font_families(preset)))
+        }
+        (None, None) if is_system_font(&font_family) => FallbackResolution::new(
+            ResolvedFallback::Manual(vec![quote_font_family(&font_family, quoting)]),
+        ),
+        (None, _) => match metrics_source.load().await {
+            Ok(font_metrics_map) => {
+                let font_family = lookup_font_family(options, &font_metrics_map);
+                // Self-hosted metrics don't need to be bundled
+                let missing_font = match options.self_hosted_metrics {
+                    Some(_) => None,
+                    None => {
+                        metrics_source
+                            .find_missing_font(
+                                &font_family,
+                                !options.disable_font_family_suggestions,
+                            )
+                            .await?
+                    }
+                };
+                match missing_font {
+                    Some(missing_font) => FallbackResolution {
+                        fallback: ResolvedFallback::Error,
+                        issues: vec![missing_metrics_issue(
+                            &options.font_family,
+                            missing_font.suggestion.as_deref(),
+                        )],
+                    },
+                    None => {
+                        let consolidated_adjustment = match get_consolidated_weight_range(options) {
+                            Some(_) => Some(average_weight_adjustments(
+                                &metrics_source.weight_font_adjustments(options).await?,
+                            )),
+                            None => None,
+                        };
+                        resolve_automatic_fallback(
+                            options,
+                            &font_family,
+                            &font_metrics_map,
+                            consolidated_adjustment,
+                        )
+                    }
+                }
+            }
+            Err(_) => FallbackResolution::new(ResolvedFallback::Error),
+        },
     };
+    // The stack of a preset is still listed when it can't be adjusted against
+    if let (None, Some(preset), ResolvedFallback::Error) =
+        (fallback, options.fallback_preset, &resolution.fallback)
+    {
+        resolution.fallback = ResolvedFallback::Manual(preset_font_families(preset));
+    }
+    if fallback.is_none() && options.fallback.is_some() {
+        resolution.issues.push(FallbackIssue {
+            title: format!("Empty fallback list for font `{}`", options.font_family),
+            description: "The `fallback` option lists no fonts, so it is ignored and the fallback \
+                          is generated as if it were omitted."
+                .to_owned(),
+            severity: IssueSeverity::Warning,
+            cause: None,
+        });
+    }
 
-    Ok(Fallback {
-        font_family: fallback.name.clone(),
-        adjustment: metrics,
-    })
+    Ok(resolution)
 }
 
-#[cfg(test)]
-mod tests {
-    use anyhow::Result;
-    use turbo_binding::turbo::tasks_fs::json::parse_json_with_source_context;
+fn preset_font_families(preset: FallbackPreset) -> Vec<String> {
+    preset
+        .font_families()
+        .iter()
+        .map(|font_family| (*font_family).to_owned())
+        .collect()
+}
 
-    use super::{FontAdjustment, FontMetricsMap};
-    use crate::next_font::google::font_fallback::{lookup_fallback, Fallback};
+/// Resolves the automatic fallback of `font_family`, the normalized name of
+/// the font requested with `options`, from `font_metrics_map`. Only the font's
+/// own entry is copied, to apply the options overriding its metrics to.
+/// `consolidated_adjustment` is the adjustment declared for all weights of the
+/// font when they are consolidated, see [[average_weight_adjustments]].
+fn resolve_automatic_fallback(
+    options: &NextFontGoogleOptions,
+    font_family: &str,
+    font_metrics_map: &FontMetricsMap,
+    consolidated_adjustment: Option<Option<FontAdjustment>>,
+) -> FallbackResolution {
+    if is_emoji_font(font_metrics_map, font_family) {
+        return FallbackResolution {
+            fallback: ResolvedFallback::Skipped,
+            issues: vec![FallbackIssue {
+                title: format!(
+                    "Skipping fallback font generation for emoji font `{}`",
+                    &options.font_family,
+                ),
+                description: "Emoji and color fonts can't be approximated by a text fallback font."
+                    .to_owned(),
+                severity: IssueSeverity::Info,
+                cause: None,
+            }],
+        };
+    }
 
-    #[test]
-    fn test_fallback_from_metrics_sans_serif() -> Result<()> {
-        let font_metrics: FontMetricsMap = parse_json_with_source_context(
-            r#"
-            {
-                "inter": {
-                    "familyName": "Inter",
-                    "category": "sans-serif",
-                    "capHeight": 2048,
-                    "ascent": 2728,
-                    "descent": -680,
-                    "lineGap": 0,
-                    "unitsPerEm": 2816,
-                    "xHeight": 1536,
-                    "xWidthAvg": 1335
-                  }
-            }
-        "#,
-        )?;
+    let metrics_key = get_metrics_key(font_family, font_metrics_map);
+    let mut font_metrics_map = FontMetricsMap(
+        font_metrics_map
+            .0
+            .get(&metrics_key)
+            .map(|metrics| (metrics_key, metrics.clone()))
+            .into_iter()
+            .collect(),
+    );
+    if let Some(self_hosted_metrics) = &options.self_hosted_metrics {
+        apply_self_hosted_metrics(&mut font_metrics_map, font_family, self_hosted_metrics);
+    }
 
-        assert_eq!(
-            lookup_fallback("Inter", font_metrics, true)?,
-            Fallback {
-                font_family: "Arial".to_owned(),
-                adjustment: Some(FontAdjustment {
-                    ascent: 0.9324334770490376,
-                    descent: -0.23242476700635833,
-                    line_gap: 0.0,
-                    size_adjust: 1.0389481114147647
-                })
-            }
+    if let Some(weight_metrics_overrides) = &options.weight_metrics_overrides {
+        apply_weight_metrics_override(
+            &mut font_metrics_map,
+            font_family,
+            get_fallback_weight(&options.weights),
+            weight_metrics_overrides,
         );
-        Ok(())
     }
 
-    #[test]
-    fn test_fallback_from_metrics_serif() -> Result<()> {
-        let font_metrics: FontMetricsMap = parse_json_with_source_context(
-            r#"
-            {
-                "robotoSlab": {
-                    "familyName": "Roboto Slab",
-                    "category": "serif",
-                    "capHeight": 1456,
-                    "ascent": 2146,
-                    "descent": -555,
-                    "lineGap": 0,
-                    "unitsPerEm": 2048,
-                    "xHeight": 1082,
-                    "xWidthAvg": 969
-                  }
+    if let Some(metrics) = font_metrics_map
+        .0
+        .get(&get_metrics_key(font_family, &font_metrics_map))
+        .filter(|metrics| !VALID_UNITS_PER_EM.contains(&metrics.units_per_em))
+    {
+        return FallbackResolution {
+            fallback: ResolvedFallback::Error,
+            issues: vec![invalid_units_per_em_issue(
+                &options.font_family,
+                metrics.units_per_em,
+            )],
+        };
+    }
+
+    match lookup_fallback(
+        font_family,
+        font_metrics_map,
+        &FallbackLookupOptions::from(options),
+    ) {
+        Ok(mut fallback) => {
+            if let Some(adjustment) = consolidated_adjustment {
+                fallback.adjustment = adjustment;
             }
-        "#,
-        )?;
 
-        assert_eq!(
-            lookup_fallback("Roboto Slab", font_metrics, true)?,
-            Fallback {
-                font_family: "Times New Roman".to_owned(),
-                adjustment: Some(FontAdjustment {
-                    ascent: 0.9239210539440684,
-                    descent: -0.23894510015794873,
-                    line_gap: 0.0,
-                    size_adjust: 1.134135387462914
-                })
+            FallbackResolution {
+                issues: std::mem::take(&mut fallback.warnings)
+                    .into_iter()
+                    .map(FallbackIssue::from)
+                    .collect(),
+                fallback: ResolvedFallback::Automatic(fallback),
             }
-        );
-        Ok(())
+        }
+        Err(_) => FallbackResolution {
+            fallback: ResolvedFallback::Error,
+            issues: vec![missing_metrics_issue(&options.font_family, None)],
+        },
+    }
+}
+
+/// The font family to declare an automatic fallback as instead of a scoped
+/// font family, if the options ask for it: its local font family.
+fn unscoped_font_family<'a>(
+    fallback: &'a Fallback,
+    options: &FallbackLookupOptions,
+) -> Option<&'a str> {
+    options
+        .unscoped_font_family
+        .then_some(fallback.font_family.as_str())
+}
+
+fn missing_metrics_issue(font_family: &str, suggestion: Option<&str>) -> FallbackIssue {
+    let mut description = "Skipping generating a fallback font.".to_owned();
+    if let Some(suggestion) = suggestion {
+        description.push_str(&format!(" Did you mean `{}`?", suggestion));
+    }
+
+    FallbackIssue {
+        title: format!(
+            "Failed to find font override values for font `{}`",
+            font_family,
+        ),
+        description,
+        severity: IssueSeverity::Warning,
+        cause: Some(IssueCause::MissingMetrics),
+    }
+}
+
+fn invalid_units_per_em_issue(font_family: &str, units_per_em: u32) -> FallbackIssue {
+    FallbackIssue {
+        title: format!(
+            "Invalid units per em in the metrics of font `{}`",
+            font_family
+        ),
+        description: format!(
+            "The metrics have {} units per em, outside of the range of {} to {} allowed by the \
+             OpenType specification. Skipping generating a fallback font.",
+            units_per_em,
+            VALID_UNITS_PER_EM.start(),
+            VALID_UNITS_PER_EM.end()
+        ),
+        severity: IssueSeverity::Warning,
+        cause: None,
+    }
+}
+
+/// A font missing from the metrics map
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct MissingFont {
+    /// The font family in the metrics map with the most similar name, if any
+    /// is similar enough
+    pub suggestion: Option<String>,
+}
+
+/// Returns `Some` if `font_family` isn't in `font_metrics_map`, with the most
+/// similar font family in it if `suggest`. See [[get_missing_font]] to only
+/// look a font up once.
+fn find_missing_font(
+    font_family: &str,
+    font_metrics_map: &FontMetricsMap,
+    suggest: bool,
+) -> Option<MissingFont> {
+    if font_metrics_map
+        .0
+        .contains_key(&get_metrics_key(font_family, font_metrics_map))
+    {
+        return None;
+    }
+
+    Some(MissingFont {
+        suggestion: suggest
+            .then(|| suggest_font_family(font_family, font_metrics_map))
+            .flatten(),
+    })
+}
+
+#[turbo_tasks::value(transparent)]
+struct OptionMissingFont(Option<MissingFont>);
+
+/// Looks `font_family` up in the bundled metrics like [[find_missing_font]],
+/// as a task keyed on the font family, so that requesting a missing font again
+/// doesn't repeat the search for a similar font family. The task reads the
+/// metrics from [[get_font_metrics_map]], so it's recomputed once they change.
+#[turbo_tasks::function]
+async fn get_missing_font(
+    context: FileSystemPathVc,
+    font_family: String,
+    suggest: bool,
+) -> Result<OptionMissingFontVc> {
+    Ok(OptionMissingFontVc::cell(find_missing_font(
+        &font_family,
+        &*get_font_metrics_map(context).await?,
+        suggest,
+    )))
+}
+
+/// Returns the font family in `font_metrics_map` with the name closest to
+/// `font_family`, if it's close enough to likely be a typo.
+fn suggest_font_family(font_family: &str, font_metrics_map: &FontMetricsMap) -> Option<String> {
+    let font_family_lowercase = font_family.to_lowercase();
+    let max_distance = (font_family.chars().count() / 3).max(1);
+
+    font_metrics_map
+        .0
+        .values()
+        .map(|metrics| {
+            (
+                levenshtein_distance(&font_family_lowercase, &metrics.family_name.to_lowercase()),
+                &metrics.family_name,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, family_name)| family_name.clone())
+}
+
+/// The number of single-character edits needed to turn `a` into `b`
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + usize::from(a_char != *b_char);
+            row.push(substitution.min(previous_row[j + 1] + 1).min(row[j] + 1));
+        }
+        previous_row = row;
+    }
+
+    previous_row[b.len()]
+}
+
+/// Returns the bundled metrics for a Google font, e.g. for tooling that needs
+/// values not used to compute the fallback such as `cap_height`. Only the
+/// font's entry is parsed, unless it's looked up by family ID, which needs the
+/// whole metrics map.
+#[turbo_tasks::function]
+pub(super) async fn get_font_metrics(
+    context: FileSystemPathVc,
+    options_vc: NextFontGoogleOptionsVc,
+) -> Result<OptionFontMetricsMapEntryVc> {
+    let options = options_vc.await?;
+    let entry = match options.family_id {
+        Some(_) => find_font_metrics(&*get_font_metrics_map(context).await?, &options),
+        None => {
+            load_font_metrics_lazily(
+                context,
+                FONT_METRICS_FILES,
+                &normalize_font_family(&options.font_family),
+            )
+            .await?
+        }
+    };
+    Ok(OptionFontMetricsMapEntryVc::cell(
+        entry.map(|entry| entry.cell()),
+    ))
+}
+
+/// The automatic fallback adjustment of each requested weight of a font, keyed
+/// by weight
+#[turbo_tasks::value(transparent)]
+pub(super) struct WeightFontAdjustments(BTreeMap<u16, Option<FontAdjustment>>);
+
+#[turbo_tasks::value(transparent)]
+struct OptionFontAdjustment(Option<FontAdjustment>);
+
+/// Computes the automatic fallback adjustment of every requested weight of a
+/// font, e.g. for a multi-weight import. Each weight is computed in its own
+/// task so they run in parallel, sharing a single lookup of the font's
+/// metrics. Variable fonts only have their fallback weight.
+#[turbo_tasks::function]
+pub(super) async fn get_weight_font_adjustments(
+    context: FileSystemPathVc,
+    options_vc: NextFontGoogleOptionsVc,
+) -> Result<WeightFontAdjustmentsVc> {
+    let metrics = get_font_metrics(context, options_vc);
+    let adjustments = adjusted_weights(&*options_vc.await?)
+        .into_iter()
+        .map(|weight| async move {
+            let adjustment = get_weight_font_adjustment(metrics, options_vc, weight as u32).await?;
+            Ok((weight, (*adjustment).clone()))
+        })
+        .try_join()
+        .await?;
+    Ok(WeightFontAdjustmentsVc::cell(
+        adjustments.into_iter().collect(),
+    ))
+}
+
+#[turbo_tasks::function]
+async fn get_weight_font_adjustment(
+    metrics: OptionFontMetricsMapEntryVc,
+    options_vc: NextFontGoogleOptionsVc,
+    weight: u32,
+) -> Result<OptionFontAdjustmentVc> {
+    let metrics = match &*metrics.await? {
+        Some(metrics) => Some((*metrics.await?).clone()),
+        None => None,
+    };
+
+    Ok(OptionFontAdjustmentVc::cell(weight_font_adjustment(
+        &*options_vc.await?,
+        metrics,
+        u16::try_from(weight)?,
+    )))
+}
+
+/// The weights of the font of `options` with an automatic fallback adjustment
+/// of their own, see [[get_weight_font_adjustments]]
+fn adjusted_weights(options: &NextFontGoogleOptions) -> Vec<u16> {
+    match &options.weights {
+        FontWeights::Variable => vec![get_fallback_weight(&options.weights)],
+        FontWeights::Fixed(weights) => weights.clone(),
+    }
+}
+
+/// The automatic fallback adjustment of the font of `options` at `weight`, from
+/// its bundled `metrics` with its self-hosted metrics applied, if any. `None`
+/// if there are no metrics or they can't be adjusted against.
+fn weight_font_adjustment(
+    options: &NextFontGoogleOptions,
+    metrics: Option<FontMetricsMapEntry>,
+    weight: u16,
+) -> Option<FontAdjustment> {
+    let metrics = match &options.self_hosted_metrics {
+        Some(self_hosted_metrics) => {
+            let font_family = normalize_font_family(&options.font_family);
+            let metrics_key = format_fallback_font_name(&font_family);
+            let mut font_metrics_map = FontMetricsMap(
+                metrics
+                    .map(|metrics| (metrics_key.clone(), metrics))
+                    .into_iter()
+                    .collect(),
+            );
+            apply_self_hosted_metrics(&mut font_metrics_map, &font_family, self_hosted_metrics);
+            font_metrics_map.0.remove(&metrics_key)
+        }
+        None => metrics,
+    }?;
+
+    lookup_weight_adjustment(options, metrics, weight)
+        .ok()
+        .flatten()
+}
+
+/// The lowest and highest requested weight, when the automatic fallback is
+/// declared once for all weights of a font with `consolidate_fallback_weights`
+fn get_consolidated_weight_range(options: &NextFontGoogleOptions) -> Option<(u16, u16)> {
+    match &options.weights {
+        FontWeights::Fixed(weights)
+            if options.consolidate_fallback_weights && weights.len() > 1 =>
+        {
+            Some((*weights.iter().min()?, *weights.iter().max()?))
+        }
+        _ => None,
+    }
+}
+
+/// The average of the automatic fallback adjustments of every requested weight
+/// of a font, from [[get_weight_font_adjustments]], for a fallback declared
+/// once for all of them. `None` if no weight needs an adjustment.
+fn average_weight_adjustments(
+    weight_adjustments: &BTreeMap<u16, Option<FontAdjustment>>,
+) -> Option<FontAdjustment> {
+    let adjustments: Vec<&FontAdjustment> = weight_adjustments.values().flatten().collect();
+    if adjustments.is_empty() {
+        return None;
+    }
+
+    let average = |field: fn(&FontAdjustment) -> f64| {
+        adjustments.iter().copied().map(field).sum::<f64>() / adjustments.len() as f64
+    };
+    Some(FontAdjustment {
+        ascent: average(|adjustment| adjustment.ascent),
+        descent: average(|adjustment| adjustment.descent),
+        line_gap: average(|adjustment| adjustment.line_gap),
+        size_adjust: average(|adjustment| adjustment.size_adjust),
+    })
+}
+
+/// The automatic fallback adjustment of the font of `options` at `weight`, from
+/// the font's `metrics` with the override for `weight`, if any.
+fn lookup_weight_adjustment(
+    options: &NextFontGoogleOptions,
+    metrics: FontMetricsMapEntry,
+    weight: u16,
+) -> Result<Option<FontAdjustment>> {
+    let font_family = normalize_font_family(&options.font_family);
+    let mut font_metrics_map = FontMetricsMap(HashMap::from([(
+        format_fallback_font_name(&font_family),
+        metrics,
+    )]));
+    if let Some(weight_metrics_overrides) = &options.weight_metrics_overrides {
+        apply_weight_metrics_override(
+            &mut font_metrics_map,
+            &font_family,
+            weight,
+            weight_metrics_overrides,
+        );
+    }
+
+    Ok(lookup_fallback(
+        &font_family,
+        font_metrics_map,
+        &FallbackLookupOptions::from(options),
+    )?
+    .adjustment)
+}
+
+/// Returns the generic font family, e.g. `sans-serif`, to end the
+/// `font-family` chain of a webfont with, based on its category.
+#[turbo_tasks::function]
+pub(super) async fn get_generic_font_family(
+    context: FileSystemPathVc,
+    options_vc: NextFontGoogleOptionsVc,
+) -> Result<StringVc> {
+    let metrics = get_font_metrics(context, options_vc).await;
+    let category = match &metrics {
+        Ok(metrics) => match &**metrics {
+            Some(metrics) => metrics.await?.category.clone(),
+            None => None,
+        },
+        Err(_) => None,
+    };
+
+    Ok(StringVc::cell(
+        generic_font_family(category.as_ref()).to_owned(),
+    ))
+}
+
+/// The generic font family for fonts of `category`, defaulting to
+/// `sans-serif` as when choosing a fallback font.
+fn generic_font_family(category: Option<&FontCategory>) -> &'static str {
+    match category {
+        Some(category) if category.categories().contains(&"monospace") => "monospace",
+        Some(category) if category.is_serif() => "serif",
+        _ => "sans-serif",
+    }
+}
+
+/// Returns the fonts of a manual `fallback` list that are neither generic font
+/// families, fonts of a [[FallbackPreset]], system fonts nor in
+/// `font_metrics_map`, ignoring quotes and case. These are likely misspelled.
+fn find_unrecognized_fallback_fonts<'a>(
+    fallback: &'a [String],
+    font_metrics_map: &FontMetricsMap,
+) -> Vec<&'a str> {
+    let unquote = |font_family: &'a str| font_family.trim().trim_matches(|c| c == '\'' || c == '"');
+    let preset_font_families = [
+        FallbackPreset::TailwindSans,
+        FallbackPreset::TailwindSerif,
+        FallbackPreset::TailwindMono,
+    ]
+    .into_iter()
+    .flat_map(|preset| {
+        preset
+            .font_families()
+            .iter()
+            .map(|font_family| unquote(font_family))
+    });
+    let known_font_families: Vec<&str> = GENERIC_FONT_FAMILIES
+        .iter()
+        .chain(SYSTEM_FONTS)
+        .copied()
+        .chain(preset_font_families)
+        .collect();
+
+    fallback
+        .iter()
+        .map(|font_family| unquote(font_family))
+        .filter(|font_family| {
+            !known_font_families
+                .iter()
+                .any(|known_font_family| known_font_family.eq_ignore_ascii_case(font_family))
+                && !font_metrics_map
+                    .0
+                    .contains_key(&get_metrics_key(font_family, font_metrics_map))
+        })
+        .collect()
+}
+
+/// A warning about a font of a manual fallback list found by
+/// [[find_unrecognized_fallback_fonts]], with the most similar font family in
+/// the metrics map, if any
+fn unrecognized_fallback_font_issue(
+    font_family: &str,
+    unrecognized_font: &str,
+    suggestion: Option<&str>,
+) -> FallbackIssue {
+    let mut description = "It is neither a generic font family, a common system font nor a Google \
+                           font, so it may be misspelled. The fallback is used as given."
+        .to_owned();
+    if let Some(suggestion) = suggestion {
+        description.push_str(&format!(" Did you mean `{}`?", suggestion));
+    }
+
+    FallbackIssue {
+        title: format!(
+            "Unrecognized font `{}` in the fallback of font `{}`",
+            unrecognized_font, font_family
+        ),
+        description,
+        severity: IssueSeverity::Warning,
+        cause: None,
+    }
+}
+
+/// Returns the entry for the font of `options`, looked up by its family ID if
+/// it has one in the metrics map, otherwise by its name as requested by the
+/// user, e.g. `Roboto Slab`.
+fn find_font_metrics(
+    font_metrics_map: &FontMetricsMap,
+    options: &NextFontGoogleOptions,
+) -> Option<FontMetricsMapEntry> {
+    let font_family = lookup_font_family(options, font_metrics_map);
+    font_metrics_map
+        .0
+        .get(&get_metrics_key(&font_family, font_metrics_map))
+        .cloned()
+}
+
+/// The normalized name of the font of `options` in `font_metrics_map`, see
+/// [[NextFontGoogleOptions::lookup_font_family]]
+fn lookup_font_family(
+    options: &NextFontGoogleOptions,
+    font_metrics_map: &FontMetricsMap,
+) -> String {
+    if options.family_id.is_none() {
+        return normalize_font_family(&options.font_family);
+    }
+
+    let family_ids: FamilyIdIndex = font_metrics_map
+        .0
+        .values()
+        .filter_map(|entry| Some((entry.family_id.clone()?, entry.family_name.clone())))
+        .collect();
+    normalize_font_family(options.lookup_font_family(&family_ids))
+}
+
+/// Whether `font_family` is categorized as an emoji font in the metrics map.
+fn is_emoji_font(font_metrics_map: &FontMetricsMap, font_family: &str) -> bool {
+    font_metrics_map
+        .0
+        .get(&get_metrics_key(font_family, font_metrics_map))
+        .and_then(|metrics| metrics.category.as_ref())
+        .map_or(false, FontCategory::is_emoji)
+}
+
+/// A metrics map borrowed from a [[MetricsSource]], e.g. read from a cell
+type MetricsRef<'a> = Box<dyn Deref<Target = FontMetricsMap> + Send + Sync + 'a>;
+
+/// Where the metrics to resolve fallbacks with are loaded from, see
+/// [[resolve_font_fallback]]
+#[async_trait]
+trait MetricsSource: Send + Sync {
+    async fn load(&self) -> Result<MetricsRef<'_>>;
+
+    /// See [[find_missing_font]]
+    async fn find_missing_font(
+        &self,
+        font_family: &str,
+        suggest: bool,
+    ) -> Result<Option<MissingFont>> {
+        Ok(find_missing_font(
+            font_family,
+            &**self.load().await?,
+            suggest,
+        ))
+    }
+
+    /// The automatic fallback adjustment of each weight of the font of
+    /// `options`, looking its metrics up once for all weights. See
+    /// [[get_weight_font_adjustments]].
+    async fn weight_font_adjustments(
+        &self,
+        options: &NextFontGoogleOptions,
+    ) -> Result<BTreeMap<u16, Option<FontAdjustment>>> {
+        let metrics = find_font_metrics(&**self.load().await?, options);
+        Ok(adjusted_weights(options)
+            .into_iter()
+            .map(|weight| {
+                (
+                    weight,
+                    weight_font_adjustment(options, metrics.clone(), weight),
+                )
+            })
+            .collect())
+    }
+}
+
+/// The metrics files bundled with Next.js, see [[get_font_metrics_map]]
+struct FileMetricsSource {
+    pub context: FileSystemPathVc,
+    /// The options of the font being resolved, to key the tasks computing
+    /// values of the font on
+    pub options_vc: NextFontGoogleOptionsVc,
+}
+
+#[async_trait]
+impl MetricsSource for FileMetricsSource {
+    async fn load(&self) -> Result<MetricsRef<'_>> {
+        Ok(Box::new(get_font_metrics_map(self.context).await?))
+    }
+
+    async fn find_missing_font(
+        &self,
+        font_family: &str,
+        suggest: bool,
+    ) -> Result<Option<MissingFont>> {
+        Ok((*get_missing_font(self.context, font_family.to_owned(), suggest).await?).clone())
+    }
+
+    async fn weight_font_adjustments(
+        &self,
+        _options: &NextFontGoogleOptions,
+    ) -> Result<BTreeMap<u16, Option<FontAdjustment>>> {
+        Ok((*get_weight_font_adjustments(self.context, self.options_vc).await?).clone())
+    }
+}
+
+/// The bundled metrics files, merged like [[load_font_metrics]]. The files are
+/// read through the turbo filesystem, so when one changes, e.g. in watch mode,
+/// this is recomputed and every fallback read from it is invalidated, even
+/// while being computed, rather than being left with the previous metrics.
+/// Values derived from the map are cached by tasks reading it, like
+/// [[get_missing_font]], for the same reason.
+#[turbo_tasks::function]
+async fn get_font_metrics_map(context: FileSystemPathVc) -> Result<FontMetricsMapVc> {
+    Ok(load_font_metrics(context, FONT_METRICS_FILES).await?.cell())
+}
+
+async fn load_font_metrics(context: FileSystemPathVc, paths: &[&str]) -> Result<FontMetricsMap> {
+    let mut font_metrics_maps: Vec<FontMetricsMap> = Vec::with_capacity(paths.len());
+    for path in paths {
+        font_metrics_maps.push(load_next_json(context, path).await?);
+    }
+
+    Ok(merge_font_metrics(font_metrics_maps))
+}
+
+/// Merges metrics maps into one. Entries of later maps replace entries of
+/// earlier ones for the same font.
+fn merge_font_metrics(font_metrics_maps: Vec<FontMetricsMap>) -> FontMetricsMap {
+    let mut merged = HashMap::new();
+    for font_metrics_map in font_metrics_maps {
+        merged.extend(font_metrics_map.0);
+    }
+
+    FontMetricsMap(merged)
+}
+
+/// Loads the metrics of `font_family` from the bundled metrics files, parsing
+/// only its entries with [[parse_font_metrics_lazily]]. Entries of later files
+/// replace those of earlier ones, like in [[merge_font_metrics]].
+async fn load_font_metrics_lazily(
+    context: FileSystemPathVc,
+    paths: &[&str],
+    font_family: &str,
+) -> Result<Option<FontMetricsMapEntry>> {
+    let mut entries = MetricsKeyEntries::default();
+    for path in paths {
+        let file_entries = load_next_file(context, path, |metrics_json| {
+            parse_metrics_key_entries(&metrics_json.to_str()?, font_family)
+        })
+        .await?;
+        entries = file_entries.or(entries);
+    }
+
+    Ok(entries.into_entry())
+}
+
+/// Parses the metrics of `font_family` from a whole metrics file, skipping the
+/// other entries without materializing them. Keys are borrowed from
+/// `metrics_json` where possible, so a single-font lookup only allocates for
+/// the font's own entry. The entry is looked up like [[get_metrics_key]].
+fn parse_font_metrics_lazily(
+    metrics_json: &str,
+    font_family: &str,
+) -> Result<Option<FontMetricsMapEntry>> {
+    Ok(parse_metrics_key_entries(metrics_json, font_family)?.into_entry())
+}
+
+/// Parses the entries of `font_family` under both keys [[get_metrics_key]] may
+/// look it up by, see [[parse_font_metrics_lazily]]
+fn parse_metrics_key_entries(metrics_json: &str, font_family: &str) -> Result<MetricsKeyEntries> {
+    let mut deserializer = serde_json::Deserializer::from_str(metrics_json);
+    let entries = FontMetricsEntrySeed {
+        font_family,
+        formatted_metrics_key: &format_fallback_font_name(font_family),
+    }
+    .deserialize(&mut deserializer)?;
+    deserializer.end()?;
+
+    Ok(entries)
+}
+
+/// The entries of a font family in a metrics map under both keys
+/// [[get_metrics_key]] may look it up by, so maps can be merged before
+/// choosing between them
+#[derive(Default)]
+struct MetricsKeyEntries {
+    /// Keyed by the font family as is, e.g. a key formatted before
+    pub unformatted: Option<FontMetricsMapEntry>,
+    /// Keyed by the font family's formatted fallback font name
+    pub formatted: Option<FontMetricsMapEntry>,
+}
+
+impl MetricsKeyEntries {
+    /// Merges the entries of a later map with those of `earlier`, replacing
+    /// them for the same key
+    fn or(self, earlier: MetricsKeyEntries) -> MetricsKeyEntries {
+        MetricsKeyEntries {
+            unformatted: self.unformatted.or(earlier.unformatted),
+            formatted: self.formatted.or(earlier.formatted),
+        }
+    }
+
+    /// The entry [[get_metrics_key]] looks up
+    fn into_entry(self) -> Option<FontMetricsMapEntry> {
+        self.unformatted.or(self.formatted)
+    }
+}
+
+/// Deserializes a metrics map into only the entries of `font_family`, see
+/// [[parse_metrics_key_entries]]
+struct FontMetricsEntrySeed<'a> {
+    font_family: &'a str,
+    formatted_metrics_key: &'a str,
+}
+
+/// A key of a metrics map, borrowed unless it contains escapes
+#[derive(Deserialize)]
+struct MetricsKey<'a>(#[serde(borrow)] Cow<'a, str>);
+
+impl<'de, 'a> DeserializeSeed<'de> for FontMetricsEntrySeed<'a> {
+    type Value = MetricsKeyEntries;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for FontMetricsEntrySeed<'a> {
+    type Value = MetricsKeyEntries;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of font metrics")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = MetricsKeyEntries::default();
+        while let Some(MetricsKey(metrics_key)) = map.next_key()? {
+            // Later entries for the same key replace earlier ones, as when
+            // parsing the whole map
+            if metrics_key == self.font_family {
+                entries.unformatted = Some(map.next_value()?);
+            } else if metrics_key == self.formatted_metrics_key {
+                entries.formatted = Some(map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Whether `font_family` has metrics in `metrics_json`, parsing either the
+/// whole metrics map or only the font's entry. Used to compare both in
+/// `benches/`, through `next_font_bench`.
+pub(crate) fn has_font_metrics(
+    metrics_json: &str,
+    font_family: &str,
+    lazily: bool,
+) -> Result<bool> {
+    Ok(if lazily {
+        parse_font_metrics_lazily(metrics_json, font_family)?.is_some()
+    } else {
+        let font_metrics_map: FontMetricsMap = serde_json::from_str(metrics_json)?;
+        find_missing_font(font_family, &font_metrics_map, false).is_none()
+    })
+}
+
+// From https://github.com/vercel/next.js/blob/1628260b88ce3052ac307a1607b6e8470188ab83/packages/next/src/server/font-utils.ts#L101
+// Its regex `(?:^\w|[A-Z]|\b\w)` was only replaced at its first match here,
+// which is always the first word character: that character is lowercased, and
+// whitespace is removed.
+pub fn format_fallback_font_name(font_family: &str) -> String {
+    let mut fallback_name = String::with_capacity(font_family.len());
+    let mut found_first_word_char = false;
+    for c in font_family.chars() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        if !found_first_word_char && (c.is_alphanumeric() || c == '_') {
+            found_first_word_char = true;
+            fallback_name.extend(c.to_lowercase());
+        } else {
+            fallback_name.push(c);
+        }
+    }
+    fallback_name
+}
+
+/// The weight of the font used to generate its fallback: the first requested
+/// weight, or normal (400) for variable fonts.
+fn get_fallback_weight(weights: &FontWeights) -> u16 {
+    match weights {
+        FontWeights::Variable => 400,
+        FontWeights::Fixed(weights) => weights.first().copied().unwrap_or(400),
+    }
+}
+
+/// Replaces the bundled metrics of `font_family` with the metrics of its
+/// self-hosted copy, adding them if none are bundled. Overrides for specific
+/// weights still apply on top of them.
+fn apply_self_hosted_metrics(
+    font_metrics_map: &mut FontMetricsMap,
+    font_family: &str,
+    self_hosted_metrics: &SelfHostedFontMetrics,
+) {
+    let metrics_key = get_metrics_key(font_family, font_metrics_map);
+    let bundled_metrics = font_metrics_map.0.remove(&metrics_key);
+    let category = match &self_hosted_metrics.category {
+        Some(category) => Some(FontCategory::One(category.clone())),
+        None => bundled_metrics
+            .as_ref()
+            .and_then(|metrics| metrics.category.clone()),
+    };
+
+    font_metrics_map.0.insert(
+        metrics_key,
+        FontMetricsMapEntry {
+            family_name: bundled_metrics.as_ref().map_or_else(
+                || font_family.to_owned(),
+                |metrics| metrics.family_name.clone(),
+            ),
+            category,
+            cap_height: bundled_metrics
+                .as_ref()
+                .map_or(0, |metrics| metrics.cap_height),
+            ascent: self_hosted_metrics.ascent,
+            descent: self_hosted_metrics.descent,
+            line_gap: self_hosted_metrics.line_gap,
+            units_per_em: self_hosted_metrics.units_per_em,
+            x_height: bundled_metrics
+                .as_ref()
+                .map_or(0, |metrics| metrics.x_height),
+            x_width_avg: self_hosted_metrics.x_width_avg.0,
+            styles: BTreeMap::new(),
+            optical_sizes: BTreeMap::new(),
+            default_optical_size: None,
+            preferred_fallback: bundled_metrics
+                .as_ref()
+                .and_then(|metrics| metrics.preferred_fallback.clone()),
+            version: None,
+            family_id: bundled_metrics.and_then(|metrics| metrics.family_id),
+        },
+    );
+}
+
+/// Replaces the bundled metrics of `font_family` with the user's override for
+/// `weight`, if one was provided.
+fn apply_weight_metrics_override(
+    font_metrics_map: &mut FontMetricsMap,
+    font_family: &str,
+    weight: u16,
+    weight_metrics_overrides: &BTreeMap<u16, FontMetricsOverride>,
+) {
+    let Some(metrics_override) = weight_metrics_overrides.get(&weight) else {
+        return;
+    };
+    let metrics_key = get_metrics_key(font_family, font_metrics_map);
+    let Some(metrics) = font_metrics_map.0.get_mut(&metrics_key) else {
+        return;
+    };
+
+    if let Some(ascent) = metrics_override.ascent {
+        metrics.ascent = ascent;
+    }
+    if let Some(descent) = metrics_override.descent {
+        metrics.descent = descent;
+    }
+    if let Some(line_gap) = metrics_override.line_gap {
+        metrics.line_gap = line_gap;
+    }
+    if let Some(units_per_em) = metrics_override.units_per_em {
+        metrics.units_per_em = units_per_em;
+    }
+    if let Some(x_width_avg) = metrics_override.x_width_avg {
+        metrics.x_width_avg = x_width_avg.0;
+    }
+}
+
+/// Replaces the fields of a computed `adjustment` given in
+/// `adjustment_override`, keeping the others.
+fn apply_adjustment_override(
+    adjustment: FontAdjustment,
+    adjustment_override: &FontAdjustmentOverride,
+) -> FontAdjustment {
+    FontAdjustment {
+        ascent: adjustment_override
+            .ascent
+            .map_or(adjustment.ascent, |ascent| ascent.0),
+        descent: adjustment_override
+            .descent
+            .map_or(adjustment.descent, |descent| descent.0),
+        line_gap: adjustment_override
+            .line_gap
+            .map_or(adjustment.line_gap, |line_gap| line_gap.0),
+        size_adjust: adjustment_override
+            .size_adjust
+            .map_or(adjustment.size_adjust, |size_adjust| size_adjust.0),
+    }
+}
+
+fn lookup_fallback(
+    font_family: &str,
+    font_metrics_map: FontMetricsMap,
+    options: &FallbackLookupOptions,
+) -> Result<Fallback> {
+    let selection = select_fallback(font_family, &font_metrics_map, options.fallback_font)?;
+    let metrics = font_metrics_map
+        .0
+        .get(&selection.metrics_key)
+        .context("Font not found in metrics")?;
+    if !VALID_UNITS_PER_EM.contains(&metrics.units_per_em) {
+        bail!(
+            "Invalid units per em {} in the metrics of `{}`",
+            metrics.units_per_em,
+            metrics.family_name
+        );
+    }
+
+    let fallback = recompute_fallback_adjustment(&selection, metrics, options);
+    debug_assert!(
+        options.adjust || (fallback.adjustment.is_none() && fallback.precise_adjustment.is_none()),
+        "Fallback for `{}` was adjusted although adjustment is disabled",
+        font_family
+    );
+
+    Ok(fallback)
+}
+
+/// What enabling `adjustFontFallback` adds to the fallback of `font_family`,
+/// for docs and reports: the overrides and `size_adjust` of its adjustment, as
+/// the fallback has none when it's disabled. `None` if even the adjusted
+/// fallback needs no adjustment. Reported in the fallback preview.
+fn adjustment_impact(
+    font_family: &str,
+    font_metrics_map: &FontMetricsMap,
+) -> Result<Option<FontAdjustment>> {
+    let selection = select_fallback(font_family, font_metrics_map)?;
+    let metrics = font_metrics_map
+        .0
+        .get(&selection.metrics_key)
+        .context("Font not found in metrics")?;
+    let unadjusted = FallbackLookupOptions {
+        adjust: false,
+        ..Default::default()
+    };
+    debug_assert!(
+        recompute_fallback_adjustment(&selection, metrics, &unadjusted)
+            .adjustment
+            .is_none()
+    );
+
+    Ok(
+        recompute_fallback_adjustment(&selection, metrics, &FallbackLookupOptions::default())
+            .adjustment,
+    )
+}
+
+/// The default fallback font chosen for a webfont, before any adjustment is
+/// computed. Depends only on the font's name and category, so it can be kept
+/// while the font's other metrics change.
+#[derive(Clone, Debug)]
+struct FallbackSelection {
+    /// The key of the webfont in the metrics map, e.g. `robotoSlab`
+    pub metrics_key: String,
+    pub fallback_font: &'static DefaultFallbackFont,
+}
+
+/// The key of `font_family` in `font_metrics_map`: `font_family` itself if it
+/// is already a key, e.g. a key formatted before, otherwise its formatted
+/// fallback font name.
+fn get_metrics_key(font_family: &str, font_metrics_map: &FontMetricsMap) -> String {
+    if font_metrics_map.0.contains_key(font_family) {
+        font_family.to_owned()
+    } else {
+        format_fallback_font_name(font_family)
+    }
+}
+
+fn select_fallback(
+    font_family: &str,
+    font_metrics_map: &FontMetricsMap,
+    fallback_font: Option<&str>,
+) -> Result<FallbackSelection> {
+    let metrics_key = get_metrics_key(font_family, font_metrics_map);
+    let metrics = font_metrics_map
+        .0
+        .get(&metrics_key)
+        .context("Font not found in metrics")?;
+
+    let preferred_fallback = fallback_font
+        .or(metrics.preferred_fallback.as_deref())
+        .and_then(find_default_fallback_font);
+
+    Ok(FallbackSelection {
+        fallback_font: match preferred_fallback {
+            Some(fallback_font) => fallback_font,
+            None if metrics
+                .category
+                .as_ref()
+                .map_or(false, FontCategory::is_serif) =>
+            {
+                &DEFAULT_SERIF_FONT
+            }
+            None => &DEFAULT_SANS_SERIF_FONT,
+        },
+        metrics_key,
+    })
+}
+
+/// Computes the fallback for a previously made [FallbackSelection] from the
+/// webfont's current `metrics`, skipping name formatting and the choice of
+/// default fallback font. Used when only a font's metrics have changed.
+fn recompute_fallback_adjustment(
+    selection: &FallbackSelection,
+    metrics: &FontMetricsMapEntry,
+    options: &FallbackLookupOptions,
+) -> Fallback {
+    let fallback = selection.fallback_font;
+    // Style metrics aren't measured per optical size, so they take precedence
+    let metrics = &metrics
+        .for_optical_size(options.optical_size)
+        .for_style(options.style.as_deref());
+    let mut warnings = vec![];
+    match &metrics.category {
+        None => warnings.push(FallbackWarning {
+            title: format!(
+                "Font metrics for `{}` have no category",
+                metrics.family_name
+            ),
+            description: "The font is assumed to be sans-serif when choosing a fallback font."
+                .to_owned(),
+        }),
+        Some(category) if category.is_empty() => warnings.push(FallbackWarning {
+            title: format!(
+                "Font metrics for `{}` have an empty category",
+                metrics.family_name
+            ),
+            description: "The font is assumed to be sans-serif when choosing a fallback font. The \
+                          metrics file likely failed to record the category."
+                .to_owned(),
+        }),
+        Some(_) => {}
+    }
+    if has_inflated_vertical_metrics(metrics) {
+        warnings.push(FallbackWarning {
+            title: format!("Font metrics for `{}` look inflated", metrics.family_name),
+            description: format!(
+                "The ascent ({}) and descent ({}) span more than {} times the units per em ({}), \
+                 so the metrics are likely wrong and the fallback may not match the font.",
+                metrics.ascent,
+                metrics.descent,
+                MAX_VERTICAL_METRICS_EM_RATIO,
+                metrics.units_per_em
+            ),
+        });
+    }
+    let font_version = metrics.version.clone();
+    let metrics = if options.adjust {
+        let size_adjust = get_blended_size_adjust(metrics, fallback, options.x_height_weight);
+        if let Some(threshold) = options.size_adjust_warning_threshold {
+            if size_adjust > threshold {
+                warnings.push(FallbackWarning {
+                    title: format!(
+                        "{} is a poor fallback font for `{}`",
+                        fallback.name, metrics.family_name
+                    ),
+                    description: format!(
+                        "The fallback font needs a size-adjust of {:.2}%, more than {:.2}%. \
+                         Consider providing a closer matching font with the `fallback` option.",
+                        size_adjust * 100.0,
+                        threshold * 100.0
+                    ),
+                });
+            }
+        }
+
+        let vertical_metrics_scale = if has_percentage_vertical_metrics(metrics) {
+            warnings.push(FallbackWarning {
+                title: format!(
+                    "Font metrics for `{}` appear to be percentages",
+                    metrics.family_name
+                ),
+                description: "The ascent, descent and line gap are much smaller than the font's \
+                              units per em, so they are treated as percentages of the font size \
+                              instead of font units."
+                    .to_owned(),
+            });
+            100.0
+        } else {
+            metrics.units_per_em as f64
+        };
+
+        let ascent = metrics.ascent as f64 / (vertical_metrics_scale * size_adjust);
+        let descent = metrics.descent as f64 / (vertical_metrics_scale * size_adjust);
+        let line_gap = if options.zero_line_gap {
+            0.0
+        } else {
+            metrics.line_gap as f64 / (vertical_metrics_scale * size_adjust)
+        };
+
+        let adjustment = FontAdjustment {
+            ascent,
+            descent,
+            line_gap,
+            size_adjust,
+        };
+        let adjustment = match options.dpi_scale {
+            Some(dpi_scale) => adjustment.with_scaled_size_adjust(dpi_scale),
+            None => adjustment,
+        };
+        let adjustment = match options.descent_ascent_ratio {
+            Some(ratio) => adjustment.with_descent_ascent_ratio(ratio),
+            None => adjustment,
+        };
+        let adjustment = match options.size_adjust_step {
+            Some(step) => adjustment.with_quantized_size_adjust(step / 100.0),
+            None => adjustment,
+        };
+        let adjustment = match options.font_size {
+            Some(font_size) => adjustment.rounded_to_pixels(font_size),
+            None => adjustment,
+        };
+        let adjustment = if options.safari_quantization {
+            adjustment.rounded_to_steps(
+                options.font_size.unwrap_or(DEFAULT_FONT_SIZE),
+                SAFARI_LAYOUT_UNIT,
+            )
+        } else {
+            adjustment
+        };
+        let adjustment = match &options.adjustment_override {
+            Some(adjustment_override) => apply_adjustment_override(adjustment, adjustment_override),
+            None => adjustment,
+        };
+
+        Some(adjustment)
+            .filter(|adjustment| !adjustment.is_identity(fallback, options.adjustment_epsilon))
+    } else {
+        None
+    };
+    let (adjustment, precise_adjustment) = match metrics {
+        Some(adjustment) if options.keep_precise_adjustment => {
+            (Some(adjustment.rounded_for_css()), Some(adjustment))
+        }
+        adjustment => (adjustment, None),
+    };
+
+    Fallback {
+        font_family: if options.generic_only {
+            fallback.generic_family.clone()
+        } else {
+            fallback.name.clone()
+        },
+        adjustment,
+        precise_adjustment,
+        warnings,
+        font_version,
+    }
+}
+
+// Derived from
+// https://github.com/vercel/next.js/blob/7bfd5829999b1d203e447d30de7e29108c31934a/packages/next/src/server/font-utils.ts#L131
+fn get_size_adjust(metrics: &FontMetricsMapEntry, fallback_font: &DefaultFallbackFont) -> f64 {
+    advance_width_ratio(metrics, fallback_font)
+}
+
+/// The ratio of the average advance width of the characters of the `target`
+/// font to that of `fallback_font`, both relative to their units per em. A
+/// ratio above 1 means the target font is the wider one. Scaling the fallback
+/// font by this ratio matches their widths, which is why it is used as
+/// `size_adjust` by default, see [[DefaultFallbackFont::size_adjust_for]].
+fn advance_width_ratio(target: &FontMetricsMapEntry, fallback_font: &DefaultFallbackFont) -> f64 {
+    fallback_font.size_adjust_for(target.x_width_avg, target.units_per_em as f64)
+}
+
+/// Blends the `size_adjust` matching the average character width with the one
+/// matching the x-height, weighing the latter with `x_height_weight`. Uses
+/// the width alone for metrics without an x-height.
+fn get_blended_size_adjust(
+    metrics: &FontMetricsMapEntry,
+    fallback_font: &DefaultFallbackFont,
+    x_height_weight: f64,
+) -> f64 {
+    let width_size_adjust = get_size_adjust(metrics, fallback_font);
+    if x_height_weight == 0.0 || metrics.x_height <= 0 {
+        return width_size_adjust;
+    }
+
+    let x_height_size_adjust = fallback_font
+        .x_height_size_adjust_for(metrics.x_height as f64, metrics.units_per_em as f64);
+    (1.0 - x_height_weight) * width_size_adjust + x_height_weight * x_height_size_adjust
+}
+
+/// The `size_adjust` `font_family` would get against `default_font` rather
+/// than the default fallback font chosen for it, for what-if tooling comparing
+/// default fonts, e.g. Arial against Georgia. Lists the candidates of
+/// [[get_fallback_candidates]].
+fn size_adjust_for_default(
+    font_family: &str,
+    font_metrics_map: &FontMetricsMap,
+    default_font: &DefaultFallbackFont,
+) -> Result<f64> {
+    let metrics = font_metrics_map
+        .0
+        .get(&get_metrics_key(font_family, font_metrics_map))
+        .context("Font not found in metrics")?;
+
+    Ok(get_size_adjust(metrics, default_font))
+}
+
+/// A fallback font considered for a webfont, with the `size_adjust` it would
+/// need to match the webfont's width.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FallbackCandidate {
+    pub font_family: String,
+    pub size_adjust: f64,
+}
+
+/// Returns every default fallback font with its `size_adjust` for
+/// `font_family`, and the index of the one needing the smallest adjustment.
+/// Helps explain why a fallback font was or wasn't a close match, in the
+/// fallback preview.
+fn get_fallback_candidates(
+    font_family: &str,
+    font_metrics_map: &FontMetricsMap,
+) -> Result<(Vec<FallbackCandidate>, usize)> {
+    let candidates = default_fallback_fonts()
+        .into_iter()
+        .map(|fallback_font| {
+            Ok(FallbackCandidate {
+                font_family: fallback_font.name.clone(),
+                size_adjust: size_adjust_for_default(font_family, font_metrics_map, fallback_font)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let nearest = candidates
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            (a.size_adjust - 1.0)
+                .abs()
+                .total_cmp(&(b.size_adjust - 1.0).abs())
+        })
+        .map(|(index, _)| index)
+        .context("At least one default fallback font is required")?;
+
+    Ok((candidates, nearest))
+}
+
+/// A step of resolving a webfont's automatic fallback
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum FallbackDecisionStep {
+    /// The requested font family
+    Input,
+    /// The key the font family is looked up by in the metrics map
+    Formatting,
+    /// The family name of the metrics found, if any
+    Lookup,
+    Category,
+    /// The default fallback font chosen for the category
+    DefaultFont,
+    Adjustment,
+}
+
+/// A node of the decision tree of [[get_fallback_decision_tree]], leading to
+/// the nodes of the following step. Serializes to a structure flow diagram
+/// tooling can render.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FallbackDecisionNode {
+    pub step: FallbackDecisionStep,
+    pub outcome: String,
+    pub children: Vec<FallbackDecisionNode>,
+}
+
+/// Returns how the automatic fallback of `font_family` is decided, step by
+/// step, for generating documentation from the fallback preview. Ends at the
+/// lookup if the font has no metrics.
+fn get_fallback_decision_tree(
+    font_family: &str,
+    font_metrics_map: &FontMetricsMap,
+    options: &FallbackLookupOptions,
+) -> Result<FallbackDecisionNode> {
+    let metrics_key = get_metrics_key(font_family, font_metrics_map);
+    let mut steps = vec![
+        (FallbackDecisionStep::Input, font_family.to_owned()),
+        (FallbackDecisionStep::Formatting, metrics_key.clone()),
+    ];
+    match font_metrics_map.0.get(&metrics_key) {
+        Some(metrics) => {
+            let selection = select_fallback(font_family, font_metrics_map)?;
+            let fallback = recompute_fallback_adjustment(&selection, metrics, options);
+            steps.extend([
+                (FallbackDecisionStep::Lookup, metrics.family_name.clone()),
+                (
+                    FallbackDecisionStep::Category,
+                    metrics.category.as_ref().map_or_else(
+                        || "none".to_owned(),
+                        |category| category.categories().join(", "),
+                    ),
+                ),
+                (
+                    FallbackDecisionStep::DefaultFont,
+                    selection.fallback_font.name.clone(),
+                ),
+                (
+                    FallbackDecisionStep::Adjustment,
+                    fallback.adjustment.as_ref().map_or_else(
+                        || "none".to_owned(),
+                        |adjustment| {
+                            build_override_properties(adjustment)
+                                .lines()
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                        },
+                    ),
+                ),
+            ]);
+        }
+        None => steps.push((FallbackDecisionStep::Lookup, "not found".to_owned())),
+    }
+
+    steps
+        .into_iter()
+        .rev()
+        .fold(None, |next, (step, outcome)| {
+            Some(FallbackDecisionNode {
+                step,
+                outcome,
+                children: next.into_iter().collect(),
+            })
+        })
+        .context("The decision tree has at least one step")
+}
+
+/// How many ems the ascent and descent of a font may span before its metrics
+/// are considered suspicious. Real fonts rarely span more than one and a half.
+const MAX_VERTICAL_METRICS_EM_RATIO: u64 = 2;
+
+/// Whether the ascent and descent of a font span more than
+/// [[MAX_VERTICAL_METRICS_EM_RATIO]] ems, indicating bad metrics data.
+fn has_inflated_vertical_metrics(metrics: &FontMetricsMapEntry) -> bool {
+    metrics.ascent.unsigned_abs() as u64 + metrics.descent.unsigned_abs() as u64
+        > MAX_VERTICAL_METRICS_EM_RATIO * metrics.units_per_em as u64
+}
+
+/// How many times larger than each of its vertical metrics the em of a font
+/// must be for them to be taken as percentages. In font units, the ascent of a
+/// font alone is most of its em, even for compact fonts.
+const MIN_PERCENTAGE_METRICS_EM_RATIO: u64 = 10;
+
+/// Whether the vertical metrics of a font are given as percentages of its em
+/// rather than in font units, as some metrics sources do: each is between 0
+/// and 100, and the em is at least [[MIN_PERCENTAGE_METRICS_EM_RATIO]] times
+/// larger.
+fn has_percentage_vertical_metrics(metrics: &FontMetricsMapEntry) -> bool {
+    let largest_metric = [
+        metrics.ascent.unsigned_abs() as u64,
+        metrics.descent.unsigned_abs() as u64,
+        metrics.line_gap as u64,
+    ]
+    .into_iter()
+    .max()
+    .unwrap_or_default();
+
+    largest_metric > 0
+        && largest_metric <= 100
+        && largest_metric * MIN_PERCENTAGE_METRICS_EM_RATIO <= metrics.units_per_em as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, VecDeque},
+        fs,
+        path::Path,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use anyhow::{bail, Context, Result};
+    use async_trait::async_trait;
+    use futures::executor::block_on;
+    use serde::Deserialize;
+    use turbo_binding::{
+        turbo::tasks_fs::{json::parse_json_with_source_context, DiskFileSystemVc, FileSystem},
+        turbopack::core::issue::{Issue, IssueSeverity, IssueVc},
+    };
+    use turbo_tasks::{primitives::U32Vc, Value};
+
+    use super::{
+        get_font_fallback, get_font_metrics, get_missing_font, get_weight_font_adjustments,
+        resolve_font_fallback, timed, FallbackIssue, FallbackResolution, FontAdjustment,
+        FontCategory, FontMetricsMap, FontMetricsMapEntry, MetricsRef, MetricsSource,
+        ResolvedFallback,
+    };
+    use crate::next_font::{
+        font_fallback::{
+            find_metrics_key_collisions, tests::INTER_ADJUSTMENT, DefaultFallbackFont,
+            FallbackMatchQuality, FontFallback, FontFallbackVc, DEFAULT_SANS_SERIF_FONT,
+            DEFAULT_SERIF_FONT,
+        },
+        google::{
+            font_fallback::{
+                adjustment_impact, advance_width_ratio, apply_adjustment_override,
+                apply_self_hosted_metrics, apply_weight_metrics_override, find_font_metrics,
+                find_missing_font, find_unrecognized_fallback_fonts, format_fallback_font_name,
+                generic_font_family, get_blended_size_adjust, get_consolidated_weight_range,
+                get_fallback_candidates, get_fallback_decision_tree, get_fallback_weight,
+                get_metrics_key, has_percentage_vertical_metrics, is_emoji_font, is_system_font,
+                levenshtein_distance, lookup_fallback, lookup_weight_adjustment,
+                merge_font_metrics, missing_metrics_issue, normalize_font_family,
+                parse_font_metrics_lazily, parse_metrics_key_entries,
+                recompute_fallback_adjustment, resolve_automatic_fallback, select_fallback,
+                size_adjust_for_default, strip_font_api_syntax, suggest_font_family,
+                unscoped_font_family, Fallback, FallbackCandidate, FallbackDecisionNode,
+                FallbackDecisionStep, FallbackLookupOptions, FallbackPreview,
+                FallbackPreviewAdjustment, FallbackWarning, MissingFont,
+            },
+            options::{
+                FallbackPreset, FontAdjustmentOverride, FontMetricsOverride, FontWeights,
+                NextFontGoogleOptions, NextFontGoogleOptionsVc, SelfHostedFontMetrics,
+            },
+            tests::{
+                create_project, font_options, issue_titles, request_options, run_in_project,
+                run_once, write_font_metrics, INTER_METRICS,
+            },
+        },
+        issue::IssueCause,
+        stylesheet::build_override_properties,
+        util::{format_scoped_font_family, FontFamilyQuoting, FontFamilyType, OrderedF64},
+    };
+
+    /// Inter's entry of [[INTER_METRICS]], for [[metrics_map]]
+    const INTER_ENTRY: (&str, &str, i32, i32, u32, u32, f64) =
+        ("Inter", "sans-serif", 2728, -680, 0, 2816, 1335.0);
+
+    /// [[INTER_METRICS]] with `fields` added to Inter's entry, e.g.
+    /// `"version": "3.019"`
+    fn inter_metrics_with_fields(fields: &str) -> String {
+        INTER_METRICS.replace(
+            r#""xWidthAvg": 1335"#,
+            &format!(r#""xWidthAvg": 1335, {}"#, fields),
+        )
+    }
+
+    /// [[INTER_METRICS]] along with `other_entries` of the metrics file, e.g.
+    /// `"lora": { ... }`
+    fn inter_metrics_with_entries(other_entries: &str) -> String {
+        let inter_metrics = INTER_METRICS
+            .trim_end()
+            .strip_suffix('}')
+            .expect("Expected the metrics to be an object");
+        format!("{}, {}}}", inter_metrics, other_entries)
+    }
+
+    /// The adjustment of Roboto Slab's metrics in these tests for Times New
+    /// Roman, checked like [[INTER_ADJUSTMENT]] against [[formula_adjustment]]
+    /// by `test_expected_adjustments_match_formula`
+    fn roboto_slab_adjustment() -> FontAdjustment {
+        FontAdjustment {
+            ascent: 0.9239210539440684,
+            descent: -0.23894510015794873,
+            line_gap: 0.0,
+            size_adjust: 1.134135387462914,
+        }
+    }
+
+    /// Recomputes the default adjustment of `metrics` for `fallback_font`
+    /// straight from the formula, independently of [[lookup_fallback]]. When
+    /// the formula changes, the expected values above are regenerated by
+    /// printing its results.
+    fn formula_adjustment(
+        metrics: &FontMetricsMapEntry,
+        fallback_font: &DefaultFallbackFont,
+    ) -> FontAdjustment {
+        let size_adjust = (metrics.x_width_avg / metrics.units_per_em as f64)
+            / (fallback_font.x_width_avg / fallback_font.units_per_em as f64);
+        let units_per_em = metrics.units_per_em as f64;
+
+        FontAdjustment {
+            ascent: metrics.ascent as f64 / (units_per_em * size_adjust),
+            descent: metrics.descent as f64 / (units_per_em * size_adjust),
+            line_gap: metrics.line_gap as f64 / (units_per_em * size_adjust),
+            size_adjust,
+        }
+    }
+
+    /// Builds a metrics map from `(family_name, category, ascent, descent,
+    /// line_gap, units_per_em, x_width_avg)` tuples, keyed like the bundled
+    /// metrics files.
+    fn metrics_map(entries: &[(&str, &str, i32, i32, u32, u32, f64)]) -> FontMetricsMap {
+        FontMetricsMap(
+            entries
+                .iter()
+                .map(
+                    |&(
+                        family_name,
+                        category,
+                        ascent,
+                        descent,
+                        line_gap,
+                        units_per_em,
+                        x_width_avg,
+                    )| {
+                        (
+                            format_fallback_font_name(family_name),
+                            FontMetricsMapEntry {
+                                family_name: family_name.to_owned(),
+                                category: Some(FontCategory::One(category.to_owned())),
+                                cap_height: 0,
+                                ascent,
+                                descent,
+                                line_gap,
+                                units_per_em,
+                                x_height: 0,
+                                x_width_avg,
+                                styles: BTreeMap::new(),
+                                optical_sizes: BTreeMap::new(),
+                                default_optical_size: None,
+                                preferred_fallback: None,
+                                version: None,
+                                family_id: None,
+                            },
+                        )
+                    },
+                )
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_expected_adjustments_match_formula() {
+        let font_metrics = metrics_map(&[
+            INTER_ENTRY,
+            ("Roboto Slab", "serif", 2146, -555, 0, 2048, 969.0),
+        ]);
+
+        for (font_family, fallback_font, expected) in [
+            ("Inter", &*DEFAULT_SANS_SERIF_FONT, INTER_ADJUSTMENT),
+            (
+                "Roboto Slab",
+                &*DEFAULT_SERIF_FONT,
+                roboto_slab_adjustment(),
+            ),
+        ] {
+            let metrics = &font_metrics.0[&format_fallback_font_name(font_family)];
+            assert_eq!(
+                formula_adjustment(metrics, fallback_font),
+                expected,
+                "Expected adjustment of `{}` is out of date",
+                font_family
+            );
+        }
+    }
+
+    /// Fallbacks recorded from upstream's JS implementation by
+    /// `scripts/record-js-font-fallbacks.js`, along with the metrics they were
+    /// computed for.
+    static JS_FALLBACKS: &str = include_str!("fixtures/js-fallbacks.json");
+
+    #[derive(Deserialize)]
+    struct JsFallbacks {
+        metrics: FontMetricsMap,
+        fallbacks: Vec<JsFallback>,
+    }
+
+    /// As returned by `getFallbackFontOverrideMetrics` of `@next/font/google`
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct JsFallback {
+        font: String,
+        fallback_font: String,
+        ascent_override: String,
+        descent_override: String,
+        line_gap_override: String,
+        size_adjust: String,
+    }
+
+    #[test]
+    fn test_matches_recorded_js_fallbacks() -> Result<()> {
+        let JsFallbacks { metrics, fallbacks } = parse_json_with_source_context(JS_FALLBACKS)?;
+        assert!(fallbacks.len() >= 12);
+
+        for js_fallback in fallbacks {
+            let fallback = lookup_fallback(
+                &js_fallback.font,
+                metrics.clone(),
+                &FallbackLookupOptions::default(),
+            )?;
+
+            assert_eq!(
+                fallback.font_family, js_fallback.fallback_font,
+                "for `{}`",
+                js_fallback.font
+            );
+            assert_eq!(
+                fallback.adjustment.as_ref().map(build_override_properties),
+                Some(format!(
+                    "ascent-override: {};\ndescent-override: {};\nline-gap-override: \
+                     {};\nsize-adjust: {};\n",
+                    js_fallback.ascent_override,
+                    js_fallback.descent_override,
+                    js_fallback.line_gap_override,
+                    js_fallback.size_adjust
+                )),
+                "for `{}`",
+                js_fallback.font
+            );
+
+            // Overrides are left out altogether when `adjustFontFallback` is
+            // false
+            let unadjusted = lookup_fallback(
+                &js_fallback.font,
+                metrics.clone(),
+                &FallbackLookupOptions {
+                    adjust: false,
+                    ..Default::default()
+                },
+            )?;
+            assert_eq!(unadjusted.font_family, js_fallback.fallback_font);
+            assert_eq!(unadjusted.adjustment, None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_from_metrics_sans_serif() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+
+        assert_eq!(
+            lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?,
+            Fallback {
+                font_family: "Arial".to_owned(),
+                adjustment: Some(INTER_ADJUSTMENT),
+                precise_adjustment: None,
+                warnings: vec![],
+                font_version: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_from_metrics_serif() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(
+            r#"
+            {
+                "robotoSlab": {
+                    "familyName": "Roboto Slab",
+                    "category": "serif",
+                    "capHeight": 1456,
+                    "ascent": 2146,
+                    "descent": -555,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1082,
+                    "xWidthAvg": 969
+                  }
+            }
+        "#,
+        )?;
+
+        assert_eq!(
+            lookup_fallback(
+                "Roboto Slab",
+                font_metrics,
+                &FallbackLookupOptions::default()
+            )?,
+            Fallback {
+                font_family: "Times New Roman".to_owned(),
+                adjustment: Some(roboto_slab_adjustment()),
+                precise_adjustment: None,
+                warnings: vec![],
+                font_version: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_is_deterministic() -> Result<()> {
+        // Simulates two independent builds: each parses its own metrics and
+        // computes the fallback from scratch.
+        let build = || -> Result<String> {
+            let font_metrics: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+
+            let fallback =
+                lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?;
+            Ok(format!(
+                "{}\n{}\n{}",
+                serde_json::to_string(&fallback)?,
+                format_scoped_font_family(&FontFamilyType::Fallback, "Inter", 0xc123b8),
+                build_override_properties(
+                    fallback
+                        .adjustment
+                        .as_ref()
+                        .expect("Inter should be adjusted")
+                ),
+            ))
+        };
+
+        let first = build()?;
+        let second = build()?;
+        assert_eq!(first.as_bytes(), second.as_bytes());
+        assert!(first.contains("__Inter_Fallback_c123b8"));
+        assert!(first.contains("size-adjust: 103.89%;"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_weight_metrics_override_only_applies_to_its_weight() -> Result<()> {
+        let font_metrics =
+            || -> Result<FontMetricsMap> { parse_json_with_source_context(INTER_METRICS) };
+        let overrides = BTreeMap::from([(
+            900,
+            FontMetricsOverride {
+                x_width_avg: Some(OrderedF64(1500.0)),
+                ..Default::default()
+            },
+        )]);
+
+        let mut regular_metrics = font_metrics()?;
+        let regular_weight = get_fallback_weight(&FontWeights::Fixed(vec![400, 900]));
+        apply_weight_metrics_override(&mut regular_metrics, "Inter", regular_weight, &overrides);
+        assert_eq!(
+            lookup_fallback("Inter", regular_metrics, &FallbackLookupOptions::default())?,
+            lookup_fallback("Inter", font_metrics()?, &FallbackLookupOptions::default())?
+        );
+
+        let mut black_metrics = font_metrics()?;
+        let black_weight = get_fallback_weight(&FontWeights::Fixed(vec![900]));
+        apply_weight_metrics_override(&mut black_metrics, "Inter", black_weight, &overrides);
+        let black_adjustment =
+            lookup_fallback("Inter", black_metrics, &FallbackLookupOptions::default())?
+                .adjustment
+                .expect("Inter should be adjusted");
+        assert_eq!(
+            black_adjustment.size_adjust,
+            (1500.0 / 2816.0) / (934.5116279069767 / 2048.0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_without_adjustment_keeps_default_font() -> Result<()> {
+        let font_metrics = || -> Result<FontMetricsMap> {
+            parse_json_with_source_context(&inter_metrics_with_entries(
+                r#"
+                "robotoSlab": {
+                    "familyName": "Roboto Slab",
+                    "category": "serif",
+                    "capHeight": 1456,
+                    "ascent": 2146,
+                    "descent": -555,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1082,
+                    "xWidthAvg": 969
+                  }
+            "#,
+            ))
+        };
+
+        // A successful lookup is what keeps `get_font_fallback` from emitting an
+        // issue, so metrics being present must still resolve without adjustment.
+        assert_eq!(
+            lookup_fallback(
+                "Inter",
+                font_metrics()?,
+                &FallbackLookupOptions {
+                    adjust: false,
+                    ..Default::default()
+                }
+            )?,
+            Fallback {
+                font_family: "Arial".to_owned(),
+                adjustment: None,
+                precise_adjustment: None,
+                warnings: vec![],
+                font_version: None,
+            }
+        );
+        assert_eq!(
+            lookup_fallback(
+                "Roboto Slab",
+                font_metrics()?,
+                &FallbackLookupOptions {
+                    adjust: false,
+                    ..Default::default()
+                }
+            )?,
+            Fallback {
+                font_family: "Times New Roman".to_owned(),
+                adjustment: None,
+                precise_adjustment: None,
+                warnings: vec![],
+                font_version: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merges_font_metrics_files() -> Result<()> {
+        let base_metrics: FontMetricsMap = parse_json_with_source_context(
+            &INTER_METRICS.replace(r#""xWidthAvg": 1335"#, r#""xWidthAvg": 1000"#),
+        )?;
+        let overriding_metrics: FontMetricsMap =
+            parse_json_with_source_context(&inter_metrics_with_entries(
+                r#"
+                "robotoSlab": {
+                    "familyName": "Roboto Slab",
+                    "category": "serif",
+                    "capHeight": 1456,
+                    "ascent": 2146,
+                    "descent": -555,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1082,
+                    "xWidthAvg": 969
+                  }
+            "#,
+            ))?;
+
+        let merged = merge_font_metrics(vec![base_metrics, overriding_metrics]);
+        assert_eq!(merged.0.len(), 2);
+        // The later file's entry for Inter wins
+        assert_eq!(merged.0["inter"].x_width_avg, 1335.0);
+        assert_eq!(
+            lookup_fallback("Roboto Slab", merged, &FallbackLookupOptions::default())?.font_family,
+            "Times New Roman"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_finds_font_metrics() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+
+        let entry = find_font_metrics(
+            &font_metrics,
+            &request_options("Inter", r#"{"weight": "400"}"#)?,
+        )
+        .expect("Inter should be found");
+        assert_eq!(entry.family_name, "Inter");
+        assert_eq!(entry.cap_height, 2048);
+        assert_eq!(entry.x_height, 1536);
+        Ok(())
+    }
+
+    #[test]
+    fn test_finds_font_metrics_by_family_id() -> Result<()> {
+        let mut font_metrics =
+            metrics_map(&[INTER_ENTRY, ("Lora", "serif", 1006, -274, 0, 1000, 468.0)]);
+        font_metrics
+            .0
+            .get_mut("inter")
+            .context("Expected Inter")?
+            .family_id = Some("inter-1".to_owned());
+        let find = |arguments: &str| -> Result<Option<String>> {
+            Ok(
+                find_font_metrics(&font_metrics, &request_options("Lora", arguments)?)
+                    .map(|entry| entry.family_name),
+            )
+        };
+
+        assert_eq!(find(r#"{"weight": "400"}"#)?, Some("Lora".to_owned()));
+        assert_eq!(
+            find(r#"{"weight": "400", "family_id": "inter-1"}"#)?,
+            Some("Inter".to_owned())
+        );
+        // Unknown IDs fall back to the font's name
+        assert_eq!(
+            find(r#"{"weight": "400", "family_id": "lora-1"}"#)?,
+            Some("Lora".to_owned())
+        );
+
+        // The fallback is resolved from the metrics the ID refers to
+        let resolution = block_on(resolve_font_fallback(
+            &InMemoryMetricsSource(font_metrics.clone()),
+            &request_options("Lora", r#"{"weight": "400", "family_id": "inter-1"}"#)?,
+            &FontFamilyQuoting::AsNeeded,
+        ))?;
+        assert_eq!(resolution.issues, vec![]);
+        assert_eq!(
+            resolution.fallback,
+            ResolvedFallback::Automatic(lookup_fallback(
+                "Inter",
+                font_metrics,
+                &FallbackLookupOptions::default(),
+            )?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_skips_negligible_adjustment() -> Result<()> {
+        // Arial's own metrics, but 0.1% wider
+        let font_metrics = || -> Result<FontMetricsMap> {
+            parse_json_with_source_context(
+                r#"
+                {
+                    "arimo": {
+                        "familyName": "Arimo",
+                        "category": "sans-serif",
+                        "capHeight": 1467,
+                        "ascent": 1854,
+                        "descent": -434,
+                        "lineGap": 67,
+                        "unitsPerEm": 2048,
+                        "xHeight": 1062,
+                        "xWidthAvg": 935.4461395349
+                      }
+                }
+            "#,
+            )
+        };
+
+        let fallback =
+            lookup_fallback("Arimo", font_metrics()?, &FallbackLookupOptions::default())?;
+        assert!(fallback.adjustment.is_some());
+
+        let fallback = lookup_fallback(
+            "Arimo",
+            font_metrics()?,
+            &FallbackLookupOptions {
+                adjustment_epsilon: 0.01,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            fallback,
+            Fallback {
+                font_family: "Arial".to_owned(),
+                adjustment: None,
+                precise_adjustment: None,
+                warnings: vec![],
+                font_version: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_generic_only_fallback() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+
+        let fallback = lookup_fallback(
+            "Inter",
+            font_metrics,
+            &FallbackLookupOptions {
+                generic_only: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(fallback.font_family, "sans-serif");
+        assert_eq!(
+            fallback.adjustment.map(|adjustment| adjustment.size_adjust),
+            Some(1.0389481114147647)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_formats_fallback_font_names() {
+        for (font_family, fallback_name) in [
+            ("Inter", "inter"),
+            ("Roboto Slab", "robotoSlab"),
+            ("ABeeZee", "aBeeZee"),
+            ("M PLUS 1p", "mPLUS1p"),
+            ("IBM Plex Mono", "iBMPlexMono"),
+            (" Leading Space", "leadingSpace"),
+            ("lower case", "lowercase"),
+            ("Open_Sans", "open_Sans"),
+            ("-Prefixed", "-prefixed"),
+        ] {
+            assert_eq!(format_fallback_font_name(font_family), fallback_name);
+        }
+    }
+
+    #[test]
+    fn test_fallback_from_percentage_metrics() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(
+            r#"
+            {
+                "inter": {
+                    "familyName": "Inter",
+                    "category": "sans-serif",
+                    "capHeight": 73,
+                    "ascent": 97,
+                    "descent": -24,
+                    "lineGap": 0,
+                    "unitsPerEm": 1000,
+                    "xHeight": 55,
+                    "xWidthAvg": 474
+                  }
+            }
+        "#,
+        )?;
+
+        let fallback = lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?;
+        let adjustment = fallback.adjustment.expect("Inter should be adjusted");
+        let size_adjust = (474.0 / 1000.0) / (934.5116279069767 / 2048.0);
+        assert_eq!(adjustment.size_adjust, size_adjust);
+        assert_eq!(adjustment.ascent, 97.0 / (100.0 * size_adjust));
+        assert_eq!(adjustment.descent, -24.0 / (100.0 * size_adjust));
+        assert_eq!(
+            fallback.warnings,
+            vec![FallbackWarning {
+                title: "Font metrics for `Inter` appear to be percentages".to_owned(),
+                description: "The ascent, descent and line gap are much smaller than the font's \
+                              units per em, so they are treated as percentages of the font size \
+                              instead of font units."
+                    .to_owned(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_metrics_are_not_percentages() {
+        let font_metrics = metrics_map(&[
+            ("Inter", "sans-serif", 97, -24, 0, 1000, 474.0),
+            // Compact fonts in font units: Bebas Neue, a condensed display
+            // font, and a pixel font drawn on a 64-unit em
+            ("Bebas Neue", "sans-serif", 900, -300, 0, 1000, 400.0),
+            ("Pixel", "monospace", 52, -12, 0, 64, 32.0),
+        ]);
+        assert!(has_percentage_vertical_metrics(&font_metrics.0["inter"]));
+        assert!(!has_percentage_vertical_metrics(
+            &font_metrics.0["bebasNeue"]
+        ));
+        assert!(!has_percentage_vertical_metrics(&font_metrics.0["pixel"]));
+    }
+
+    #[test]
+    fn test_lists_fallback_candidates() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(
+            r#"
+            {
+                "inter": {
+                    "familyName": "Inter",
+                    "category": "sans-serif",
+                    "capHeight": 2048,
+                    "ascent": 2728,
+                    "descent": -680,
+                    "lineGap": 0,
+                    "unitsPerEm": 2816,
+                    "xHeight": 1536,
+                    "xWidthAvg": 1335
+                  }
+            }
+        "#,
+        )?;
+
+        let (candidates, nearest) = get_fallback_candidates("Inter", &font_metrics)?;
+        assert_eq!(
+            candidates,
+            vec![
+                FallbackCandidate {
+                    font_family: "Arial".to_owned(),
+                    size_adjust: INTER_ADJUSTMENT.size_adjust,
+                },
+                FallbackCandidate {
+                    font_family: "Times New Roman".to_owned(),
+                    size_adjust: (1335.0 / 2816.0) / (854.3953488372093 / 2048.0),
+                },
+            ]
+        );
+        assert_eq!(candidates[nearest].font_family, "Arial");
+        Ok(())
+    }
+
+    #[test]
+    fn test_font_category_forms() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(
+            r#"
+            {
+                "robotoSlab": {
+                    "familyName": "Roboto Slab",
+                    "category": ["serif", "display"],
+                    "capHeight": 1456,
+                    "ascent": 2146,
+                    "descent": -555,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1082,
+                    "xWidthAvg": 969
+                  }
+            }
+        "#,
+        )?;
+        assert_eq!(
+            font_metrics.0["robotoSlab"].category,
+            Some(FontCategory::Many(vec![
+                "serif".to_owned(),
+                "display".to_owned()
+            ]))
+        );
+        assert_eq!(
+            lookup_fallback(
+                "Roboto Slab",
+                font_metrics,
+                &FallbackLookupOptions::default()
+            )?
+            .font_family,
+            "Times New Roman"
+        );
+
+        assert!(FontCategory::One("serif".to_owned()).is_serif());
+        assert!(FontCategory::One("display, serif".to_owned()).is_serif());
+        assert!(!FontCategory::One("sans-serif".to_owned()).is_serif());
+        assert!(
+            !FontCategory::Many(vec!["sans-serif".to_owned(), "display".to_owned()]).is_serif()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_with_dpi_scale() -> Result<()> {
+        let fallback = lookup_fallback(
+            "Inter",
+            metrics_map(&[INTER_ENTRY]),
+            &FallbackLookupOptions {
+                dpi_scale: Some(1.05),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            fallback.adjustment.map(|adjustment| adjustment.size_adjust),
+            Some(1.0389481114147647 * 1.05)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_system_font() {
+        assert!(is_system_font("Arial"));
+        assert!(is_system_font("times new roman"));
+        assert!(!is_system_font("Inter"));
+        assert!(!is_system_font("Roboto Slab"));
+    }
+
+    #[test]
+    fn test_recomputes_adjustment_when_width_changes() -> Result<()> {
+        let parse_font_metrics_map =
+            || -> Result<FontMetricsMap> { parse_json_with_source_context(INTER_METRICS) };
+
+        let options = FallbackLookupOptions::default();
+        let original = lookup_fallback("Inter", parse_font_metrics_map()?, &options)?;
+
+        let mut font_metrics_map = parse_font_metrics_map()?;
+        let selection = select_fallback("Inter", &font_metrics_map, None)?;
+        let metrics = font_metrics_map.0.get_mut("inter").unwrap();
+        metrics.x_width_avg = 1400.0;
+        let recomputed = recompute_fallback_adjustment(&selection, metrics, &options);
+
+        assert_eq!(recomputed.font_family, original.font_family);
+        let original_size_adjust = original.adjustment.map(|a| a.size_adjust).unwrap();
+        let recomputed_size_adjust = recomputed.adjustment.map(|a| a.size_adjust).unwrap();
+        assert!((recomputed_size_adjust - original_size_adjust * 1400.0 / 1335.0).abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_strips_font_api_syntax() -> Result<()> {
+        assert_eq!(strip_font_api_syntax("Roboto:wght@400"), "Roboto");
+        assert_eq!(strip_font_api_syntax("Open+Sans"), "Open Sans");
+        assert_eq!(strip_font_api_syntax("Roboto Slab"), "Roboto Slab");
+
+        let parse_font_metrics_map = || -> Result<FontMetricsMap> {
+            parse_json_with_source_context(
+                r#"
+            {
+                "roboto": {
+                    "familyName": "Roboto",
+                    "category": "sans-serif",
+                    "capHeight": 1456,
+                    "ascent": 1900,
+                    "descent": -500,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1082,
+                    "xWidthAvg": 911
+                },
+                "openSans": {
+                    "familyName": "Open Sans",
+                    "category": "sans-serif",
+                    "capHeight": 1462,
+                    "ascent": 2189,
+                    "descent": -600,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1096,
+                    "xWidthAvg": 1030
+                }
+            }
+        "#,
+            )
+        };
+
+        let options = FallbackLookupOptions::default();
+        assert!(lookup_fallback(
+            &strip_font_api_syntax("Roboto:wght@400"),
+            parse_font_metrics_map()?,
+            &options
+        )
+        .is_ok());
+        assert!(lookup_fallback(
+            &strip_font_api_syntax("Open+Sans"),
+            parse_font_metrics_map()?,
+            &options
+        )
+        .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_with_zero_line_gap() -> Result<()> {
+        let font_metrics = metrics_map(&[("Roboto", "sans-serif", 1900, -500, 100, 2048, 911.0)]);
+
+        let adjustment = lookup_fallback(
+            "Roboto",
+            font_metrics,
+            &FallbackLookupOptions {
+                zero_line_gap: true,
+                ..Default::default()
+            },
+        )?
+        .adjustment
+        .context("Expected an adjustment")?;
+        assert_eq!(adjustment.line_gap, 0.0);
+        assert_ne!(adjustment.ascent, 0.0);
+        assert_ne!(adjustment.descent, 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_preview() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+
+        let fallback = lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?;
+        let preview = FallbackPreview::new(
+            format_scoped_font_family(&FontFamilyType::Fallback, "Inter", 0xc123b8),
+            &fallback,
+        );
+
+        assert_eq!(
+            preview,
+            FallbackPreview {
+                scoped_font_family: "__Inter_Fallback_c123b8".to_owned(),
+                local_font_family: "Arial".to_owned(),
+                adjustment: Some(FallbackPreviewAdjustment {
+                    ascent: 0.9324334770490376,
+                    descent: -0.23242476700635833,
+                    line_gap: 0.0,
+                    size_adjust: 1.0389481114147647,
+                    line_box_sum: 1.1648582440553958,
+                    match_quality: Some(FallbackMatchQuality::B),
+                }),
+                content_hash: None,
+                candidates: vec![],
+                nearest_candidate: None,
+                adjustment_impact: None,
+                decision_tree: None,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&preview)?,
+            r#"{"scopedFontFamily":"__Inter_Fallback_c123b8","localFontFamily":"Arial","adjustment":{"ascent":0.9324334770490376,"descent":-0.23242476700635833,"lineGap":0.0,"sizeAdjust":1.0389481114147647,"lineBoxSum":1.1648582440553958,"matchQuality":"B"}}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_without_category() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(
+            &INTER_METRICS.replace(r#""category": "sans-serif","#, ""),
+        )?;
+        assert_eq!(font_metrics.0["inter"].category, None);
+
+        let fallback = lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?;
+        assert_eq!(fallback.font_family, "Arial");
+        assert_eq!(
+            fallback.warnings,
+            vec![FallbackWarning {
+                title: "Font metrics for `Inter` have no category".to_owned(),
+                description: "The font is assumed to be sans-serif when choosing a fallback font."
+                    .to_owned(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_with_empty_category() -> Result<()> {
+        let font_metrics = |category: &str| -> Result<FontMetricsMap> {
+            parse_json_with_source_context(&INTER_METRICS.replace(
+                r#""category": "sans-serif""#,
+                &format!(r#""category": {}"#, category),
+            ))
+        };
+        let warning_titles = |category: &str| -> Result<Vec<String>> {
+            Ok(
+                lookup_fallback("Inter", font_metrics(category)?, &Default::default())?
+                    .warnings
+                    .into_iter()
+                    .map(|warning| warning.title)
+                    .collect(),
+            )
+        };
+
+        for empty_category in [r#""""#, r#"" ""#, "[]", r#"[""]"#] {
+            assert!(font_metrics(empty_category)?.0["inter"]
+                .category
+                .as_ref()
+                .map_or(false, FontCategory::is_empty));
+            assert_eq!(
+                warning_titles(empty_category)?,
+                vec!["Font metrics for `Inter` have an empty category"],
+                "for {}",
+                empty_category
+            );
+        }
+        assert_eq!(warning_titles(r#""sans-serif""#)?, Vec::<String>::new());
+        assert_eq!(
+            lookup_fallback("Inter", font_metrics(r#""""#)?, &Default::default())?.font_family,
+            "Arial"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_detects_emoji_fonts() -> Result<()> {
+        let font_metrics: FontMetricsMap =
+            parse_json_with_source_context(&inter_metrics_with_entries(
+                r#"
+                "notoColorEmoji": {
+                    "familyName": "Noto Color Emoji",
+                    "category": "emoji",
+                    "capHeight": 1900,
+                    "ascent": 1900,
+                    "descent": -500,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1900,
+                    "xWidthAvg": 2550
+                }
+            "#,
+            ))?;
+
+        assert!(is_emoji_font(&font_metrics, "Noto Color Emoji"));
+        assert!(!is_emoji_font(&font_metrics, "Inter"));
+        assert!(!is_emoji_font(&font_metrics, "Unknown"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_keeps_precise_adjustment() -> Result<()> {
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+
+        let fallback = lookup_fallback(
+            "Inter",
+            font_metrics,
+            &FallbackLookupOptions {
+                keep_precise_adjustment: true,
+                ..Default::default()
+            },
+        )?;
+        let adjustment = fallback.adjustment.context("Expected an adjustment")?;
+        let precise_adjustment = fallback
+            .precise_adjustment
+            .context("Expected a precise adjustment")?;
+
+        assert_eq!(precise_adjustment, INTER_ADJUSTMENT);
+        assert_eq!(
+            adjustment,
+            FontAdjustment {
+                ascent: 0.9324,
+                descent: -0.2324,
+                line_gap: 0.0,
+                size_adjust: 1.0389
+            }
+        );
+        // Rounding as rendered doesn't change the rendered CSS
+        assert_eq!(
+            build_override_properties(&adjustment),
+            build_override_properties(&precise_adjustment)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalizes_redundant_prefixes() -> Result<()> {
+        assert_eq!(normalize_font_family("Google Fonts: Inter"), "Inter");
+        assert_eq!(
+            normalize_font_family("google font - Roboto:wght@400"),
+            "Roboto"
+        );
+        assert_eq!(normalize_font_family("GF Open+Sans"), "Open Sans");
+        // Only prefixes followed by a separator are stripped
+        assert_eq!(normalize_font_family("GFS Didot"), "GFS Didot");
+        assert_eq!(normalize_font_family("Google Sans"), "Google Sans");
+
+        let font_metrics: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+        assert_eq!(
+            lookup_fallback(
+                &normalize_font_family("Google Fonts Inter"),
+                font_metrics,
+                &FallbackLookupOptions::default()
+            )?
+            .font_family,
+            "Arial"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_with_safari_quantization() -> Result<()> {
+        let parse_font_metrics_map =
+            || -> Result<FontMetricsMap> { parse_json_with_source_context(INTER_METRICS) };
+
+        let default_adjustment =
+            lookup_fallback("Inter", parse_font_metrics_map()?, &Default::default())?
+                .adjustment
+                .context("Expected an adjustment")?;
+        let safari_adjustment = lookup_fallback(
+            "Inter",
+            parse_font_metrics_map()?,
+            &FallbackLookupOptions {
+                safari_quantization: true,
+                ..Default::default()
+            },
+        )?
+        .adjustment
+        .context("Expected an adjustment")?;
+
+        assert_ne!(safari_adjustment.ascent, default_adjustment.ascent);
+        assert_ne!(safari_adjustment.descent, default_adjustment.descent);
+        assert_eq!(
+            safari_adjustment.size_adjust,
+            default_adjustment.size_adjust
+        );
+
+        // Every override is a whole number of 1/64 pixels at 16 pixels
+        let layout_units = safari_adjustment.ascent * safari_adjustment.size_adjust * 16.0 * 64.0;
+        assert!((layout_units - layout_units.round()).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_with_descent_ascent_ratio() -> Result<()> {
+        let adjustment = lookup_fallback(
+            "Inter",
+            metrics_map(&[INTER_ENTRY]),
+            &FallbackLookupOptions {
+                descent_ascent_ratio: Some(0.25),
+                ..Default::default()
+            },
+        )?
+        .adjustment
+        .context("Expected an adjustment")?;
+        let unconstrained = INTER_ADJUSTMENT;
+
+        assert!((adjustment.descent / adjustment.ascent + 0.25).abs() < 1e-9);
+        assert!(
+            (adjustment.ascent
+                - adjustment.descent
+                - (unconstrained.ascent - unconstrained.descent))
+                .abs()
+                < 1e-9
+        );
+        assert_eq!(adjustment.size_adjust, unconstrained.size_adjust);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_with_size_adjust_step() -> Result<()> {
+        let lookup = |x_width_avg: f64, size_adjust_step: Option<f64>| {
+            lookup_fallback(
+                "Inter",
+                metrics_map(&[("Inter", "sans-serif", 2728, -680, 0, 2816, x_width_avg)]),
+                &FallbackLookupOptions {
+                    size_adjust_step,
+                    ..Default::default()
+                },
+            )?
+            .adjustment
+            .context("Expected an adjustment")
+        };
+
+        assert_ne!(
+            lookup(1335.0, None)?.size_adjust,
+            lookup(1336.0, None)?.size_adjust
+        );
+
+        let quantized = lookup(1335.0, Some(0.5))?;
+        assert_eq!(
+            quantized.size_adjust,
+            lookup(1336.0, Some(0.5))?.size_adjust
+        );
+        assert!((quantized.size_adjust - 1.075).abs() < 1e-9);
+
+        // The line box keeps its height
+        let unquantized = lookup(1335.0, None)?;
+        assert!(
+            (quantized.ascent * quantized.size_adjust
+                - unquantized.ascent * unquantized.size_adjust)
+                .abs()
+                < 1e-9
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_generic_font_family_for_category() {
+        let category = |category: &str| Some(FontCategory::One(category.to_owned()));
+
+        assert_eq!(
+            generic_font_family(category("sans-serif").as_ref()),
+            "sans-serif"
+        );
+        assert_eq!(generic_font_family(category("serif").as_ref()), "serif");
+        assert_eq!(
+            generic_font_family(category("monospace").as_ref()),
+            "monospace"
+        );
+        assert_eq!(
+            generic_font_family(category("display").as_ref()),
+            "sans-serif"
+        );
+        assert_eq!(generic_font_family(None), "sans-serif");
+    }
+
+    #[test]
+    fn test_fallback_uses_style_metrics() -> Result<()> {
+        let parse_font_metrics_map = || -> Result<FontMetricsMap> {
+            parse_json_with_source_context(&inter_metrics_with_fields(
+                r#"
+                "styles": {
+                    "italic": {
+                        "xWidthAvg": 1280
+                    }
+                }
+            "#,
+            ))
+        };
+
+        let size_adjust = |style: Option<&str>| -> Result<f64> {
+            lookup_fallback(
+                "Inter",
+                parse_font_metrics_map()?,
+                &FallbackLookupOptions {
+                    style: style.map(str::to_owned),
+                    ..Default::default()
+                },
+            )?
+            .adjustment
+            .map(|adjustment| adjustment.size_adjust)
+            .context("Expected an adjustment")
+        };
+
+        assert_eq!(size_adjust(None)?, 1.0389481114147647);
+        assert_eq!(size_adjust(Some("normal"))?, 1.0389481114147647);
+        assert!(
+            (size_adjust(Some("italic"))? - 1.0389481114147647 * 1280.0 / 1335.0).abs() < 1e-12
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggests_similar_font_family() {
+        let font_metrics = metrics_map(&[
+            INTER_ENTRY,
+            ("Roboto Slab", "serif", 2146, -555, 0, 2048, 966.0),
+        ]);
+
+        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+        assert_eq!(
+            suggest_font_family("Roboto Slub", &font_metrics),
+            Some("Roboto Slab".to_owned())
+        );
+        assert_eq!(suggest_font_family("Lora", &font_metrics), None);
+    }
+
+    #[test]
+    fn test_parses_font_metrics_lazily() -> Result<()> {
+        let metrics_json = &inter_metrics_with_entries(
+            r#"
+                "lora": ["not", "font", "metrics"],
+                "roboto\u0053lab": {
+                    "familyName": "Roboto Slab",
+                    "category": "serif",
+                    "capHeight": 1456,
+                    "ascent": 2146,
+                    "descent": -555,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1082,
+                    "xWidthAvg": 969
+                }
+            "#,
+        );
+        let font_metrics_map: FontMetricsMap = parse_json_with_source_context(
+            &metrics_json.replace(r#""lora": ["not", "font", "metrics"],"#, ""),
+        )?;
+
+        assert_eq!(
+            parse_font_metrics_lazily(metrics_json, "Inter")?,
+            find_font_metrics(
+                &font_metrics_map,
+                &request_options("Inter", r#"{"weight": "400"}"#)?
+            )
+        );
+        // Escaped keys are compared unescaped
+        assert_eq!(
+            parse_font_metrics_lazily(metrics_json, "Roboto Slab")?,
+            find_font_metrics(
+                &font_metrics_map,
+                &request_options("Roboto Slab", r#"{"weight": "400"}"#)?
+            )
+        );
+        // Other entries are skipped without being parsed as metrics
+        assert!(parse_font_metrics_lazily(metrics_json, "Lora").is_err());
+        assert_eq!(parse_font_metrics_lazily(metrics_json, "Lato")?, None);
+        assert!(parse_font_metrics_lazily("{} {}", "Inter").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_warns_about_large_size_adjust() -> Result<()> {
+        let font_metrics = || metrics_map(&[("Wide", "sans-serif", 1854, -434, 67, 2048, 1100.0)]);
+        let options = FallbackLookupOptions {
+            size_adjust_warning_threshold: Some(1.15),
+            ..Default::default()
+        };
+
+        let fallback = lookup_fallback("Wide", font_metrics(), &options)?;
+        assert_eq!(
+            fallback.warnings,
+            vec![FallbackWarning {
+                title: "Arial is a poor fallback font for `Wide`".to_owned(),
+                description: "The fallback font needs a size-adjust of 117.71%, more than \
+                              115.00%. Consider providing a closer matching font with the \
+                              `fallback` option."
+                    .to_owned(),
+            }]
+        );
+
+        let fallback = lookup_fallback("Wide", font_metrics(), &FallbackLookupOptions::default())?;
+        assert_eq!(fallback.warnings, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unscoped_fallback_font_family() -> Result<()> {
+        let font_metrics = || metrics_map(&[INTER_ENTRY]);
+
+        let options = FallbackLookupOptions {
+            unscoped_font_family: true,
+            ..Default::default()
+        };
+        let fallback = lookup_fallback("Inter", font_metrics(), &options)?;
+        assert_eq!(unscoped_font_family(&fallback, &options), Some("Arial"));
+
+        let options = FallbackLookupOptions::default();
+        let fallback = lookup_fallback("Inter", font_metrics(), &options)?;
+        assert_eq!(unscoped_font_family(&fallback, &options), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolves_fallback_issues_without_emitting() -> Result<()> {
+        let options = request_options("Intr", r#"{"weight": "400"}"#)?;
+        let font_metrics = || metrics_map(&[INTER_ENTRY]);
+
+        assert_eq!(
+            block_on(resolve_font_fallback(
+                &InMemoryMetricsSource(font_metrics()),
+                &options,
+                &FontFamilyQuoting::AsNeeded,
+            ))?,
+            FallbackResolution {
+                fallback: ResolvedFallback::Error,
+                issues: vec![FallbackIssue {
+                    title: "Failed to find font override values for font `Intr`".to_owned(),
+                    description: "Skipping generating a fallback font. Did you mean `Inter`?"
+                        .to_owned(),
+                    severity: IssueSeverity::Warning,
+                    cause: Some(IssueCause::MissingMetrics),
+                }],
+            }
+        );
+
+        let resolution = resolve_automatic_fallback(&options, "Inter", &font_metrics(), None);
+        assert!(matches!(
+            resolution.fallback,
+            ResolvedFallback::Automatic(Fallback { ref font_family, .. }) if font_family == "Arial"
+        ));
+        assert_eq!(resolution.issues, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_with_large_units_per_em() -> Result<()> {
+        let font_metrics = metrics_map(&[("Inter", "sans-serif", 15872, -3956, 0, 16384, 7767.0)]);
+        let adjustment = lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?
+            .adjustment
+            .context("Expected an adjustment")?;
+
+        let size_adjust = (7767.0 / 16384.0) / (934.5116279069767 / 2048.0);
+        assert_eq!(adjustment.size_adjust, size_adjust);
+        assert_eq!(adjustment.ascent, 15872.0 / (16384.0 * size_adjust));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_absurd_units_per_em() {
+        let font_metrics =
+            metrics_map(&[("Inter", "sans-serif", 2728, -680, 0, u32::MAX - 1, 1335.0)]);
+        let Err(err) = lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default()) else {
+            panic!("Expected the lookup to fail");
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid units per em 4294967294 in the metrics of `Inter`"
+        );
+    }
+
+    #[test]
+    fn test_reports_invalid_units_per_em() -> Result<()> {
+        let options = request_options("Inter", r#"{"weight": "400"}"#)?;
+        let font_metrics =
+            metrics_map(&[("Inter", "sans-serif", 2728, -680, 0, u32::MAX - 1, 1335.0)]);
+
+        assert_eq!(
+            resolve_automatic_fallback(&options, "Inter", &font_metrics, None),
+            FallbackResolution {
+                fallback: ResolvedFallback::Error,
+                issues: vec![FallbackIssue {
+                    title: "Invalid units per em in the metrics of font `Inter`".to_owned(),
+                    description: "The metrics have 4294967294 units per em, outside of the range \
+                                  of 16 to 16384 allowed by the OpenType specification. Skipping \
+                                  generating a fallback font."
+                        .to_owned(),
+                    severity: IssueSeverity::Warning,
+                    cause: None,
+                }],
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_overrides_single_adjustment_field() -> Result<()> {
+        let font_metrics = || metrics_map(&[INTER_ENTRY]);
+        let automatic =
+            lookup_fallback("Inter", font_metrics(), &FallbackLookupOptions::default())?
+                .adjustment
+                .context("Expected an adjustment")?;
+
+        let options = FallbackLookupOptions {
+            adjustment_override: Some(FontAdjustmentOverride {
+                line_gap: Some(OrderedF64(0.1)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let overridden = lookup_fallback("Inter", font_metrics(), &options)?
+            .adjustment
+            .context("Expected an adjustment")?;
+
+        assert_eq!(
+            overridden,
+            FontAdjustment {
+                line_gap: 0.1,
+                ..automatic.clone()
+            }
+        );
+        assert_eq!(
+            apply_adjustment_override(automatic.clone(), &FontAdjustmentOverride::default()),
+            automatic
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_self_hosted_metrics_take_precedence() -> Result<()> {
+        let font_metrics = || metrics_map(&[INTER_ENTRY]);
+        let self_hosted_metrics = SelfHostedFontMetrics {
+            category: None,
+            ascent: 1984,
+            descent: -494,
+            line_gap: 0,
+            units_per_em: 2048,
+            x_width_avg: OrderedF64(1000.0),
+        };
+        let size_adjust = |font_metrics| -> Result<f64> {
+            lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?
+                .adjustment
+                .map(|adjustment| adjustment.size_adjust)
+                .context("Expected an adjustment")
+        };
+        let expected_size_adjust = (1000.0 / 2048.0) / (934.5116279069767 / 2048.0);
+
+        let mut self_hosted = font_metrics();
+        apply_self_hosted_metrics(&mut self_hosted, "Inter", &self_hosted_metrics);
+        assert_eq!(self_hosted.0["inter"].family_name, "Inter");
+        assert_eq!(
+            self_hosted.0["inter"].category,
+            Some(FontCategory::One("sans-serif".to_owned()))
+        );
+        assert_eq!(size_adjust(font_metrics())?, 1.0389481114147647);
+        assert_eq!(size_adjust(self_hosted)?, expected_size_adjust);
+
+        // Self-hosted metrics are used for fonts without bundled metrics too
+        let mut unbundled = metrics_map(&[]);
+        apply_self_hosted_metrics(&mut unbundled, "Inter", &self_hosted_metrics);
+        assert_eq!(size_adjust(unbundled)?, expected_size_adjust);
+        Ok(())
+    }
+
+    #[test]
+    fn test_adjustment_impact() -> Result<()> {
+        let font_metrics = metrics_map(&[("Inter", "sans-serif", 2728, -680, 0, 2816, 1335.0)]);
+
+        assert_eq!(
+            adjustment_impact("Inter", &font_metrics)?,
+            Some(INTER_ADJUSTMENT)
+        );
+        assert!(adjustment_impact("Lora", &font_metrics).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bundled_font_metrics_have_distinct_metrics_keys() -> Result<()> {
+        // Written by the `capsize_metrics` task of the build of packages/next
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../../next/dist/server/capsize-font-metrics.json");
+        let font_metrics: FontMetricsMap =
+            parse_json_with_source_context(&fs::read_to_string(&path).with_context(|| {
+                format!(
+                    "Expected the font metrics bundled with Next.js at {}",
+                    path.display()
+                )
+            })?)?;
+
+        assert_eq!(
+            find_metrics_key_collisions(font_metrics.0.values().map(|entry| (
+                format_fallback_font_name(&entry.family_name),
+                entry.family_name.as_str()
+            ))),
+            BTreeMap::new()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_blends_width_and_x_height_size_adjust() -> Result<()> {
+        let mut font_metrics = metrics_map(&[INTER_ENTRY]);
+        font_metrics
+            .0
+            .get_mut("inter")
+            .context("Expected Inter")?
+            .x_height = 1536;
+        let metrics = &font_metrics.0["inter"];
+
+        let width_size_adjust = 1.0389481114147647;
+        let x_height_size_adjust = (1536.0 / 2816.0) / (1062.0 / 2048.0);
+        assert_eq!(
+            get_blended_size_adjust(metrics, &DEFAULT_SANS_SERIF_FONT, 0.0),
+            width_size_adjust
+        );
+        assert!(
+            (get_blended_size_adjust(metrics, &DEFAULT_SANS_SERIF_FONT, 0.5)
+                - (width_size_adjust + x_height_size_adjust) / 2.0)
+                .abs()
+                < 1e-12
+        );
+
+        let blended = lookup_fallback(
+            "Inter",
+            font_metrics,
+            &FallbackLookupOptions {
+                x_height_weight: 0.5,
+                ..Default::default()
+            },
+        )?
+        .adjustment
+        .context("Expected an adjustment")?;
+        assert!(blended.size_adjust > width_size_adjust);
+        Ok(())
+    }
+
+    #[test]
+    fn test_never_adjusts_when_adjustment_is_disabled() -> Result<()> {
+        let entries = [
+            INTER_ENTRY,
+            ("Lora", "serif", 1006, -274, 0, 1000, 468.0),
+            ("Roboto Mono", "monospace", 2146, -555, 0, 2048, 1229.0),
+            ("Lobster", "display", 1000, -250, 0, 1000, 440.0),
+            ("Caveat", "handwriting", 960, -300, 0, 1000, 357.0),
+        ];
+        let options = FallbackLookupOptions {
+            adjust: false,
+            keep_precise_adjustment: true,
+            adjustment_override: Some(FontAdjustmentOverride {
+                size_adjust: Some(OrderedF64(1.1)),
+                ..Default::default()
+            }),
+            x_height_weight: 1.0,
+            ..Default::default()
+        };
+
+        for (family_name, ..) in entries {
+            let fallback = lookup_fallback(family_name, metrics_map(&entries), &options)?;
+            assert_eq!(fallback.adjustment, None, "{}", family_name);
+            assert_eq!(fallback.precise_adjustment, None, "{}", family_name);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_exports_fallback_decision_tree() -> Result<()> {
+        let font_metrics = metrics_map(&[("Inter", "sans-serif", 2728, -680, 0, 2816, 1335.0)]);
+
+        let mut steps = vec![];
+        let mut node = Some(get_fallback_decision_tree(
+            "Inter",
+            &font_metrics,
+            &FallbackLookupOptions::default(),
+        )?);
+        while let Some(FallbackDecisionNode {
+            step,
+            outcome,
+            mut children,
+        }) = node
+        {
+            steps.push((step, outcome));
+            node = children.pop();
+            assert!(children.is_empty());
+        }
+
+        assert_eq!(
+            steps,
+            vec![
+                (FallbackDecisionStep::Input, "Inter".to_owned()),
+                (FallbackDecisionStep::Formatting, "inter".to_owned()),
+                (FallbackDecisionStep::Lookup, "Inter".to_owned()),
+                (FallbackDecisionStep::Category, "sans-serif".to_owned()),
+                (FallbackDecisionStep::DefaultFont, "Arial".to_owned()),
+                (
+                    FallbackDecisionStep::Adjustment,
+                    "ascent-override: 93.24%; descent-override: 23.24%; line-gap-override: 0.00%; \
+                     size-adjust: 103.89%;"
+                        .to_owned()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_looks_up_preformatted_metrics_keys_as_is() -> Result<()> {
+        let font_metrics = || metrics_map(&[INTER_ENTRY]);
+        assert_eq!(
+            lookup_fallback("inter", font_metrics(), &FallbackLookupOptions::default())?,
+            lookup_fallback("Inter", font_metrics(), &FallbackLookupOptions::default())?
+        );
+
+        // Formatting would lowercase a key that isn't in camelCase
+        let mut font_metrics = font_metrics();
+        let entry = font_metrics.0.remove("inter").context("Expected Inter")?;
+        font_metrics.0.insert("Inter".to_owned(), entry);
+        assert_eq!(get_metrics_key("Inter", &font_metrics), "Inter");
+        assert!(lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default()).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_finds_and_parses_preformatted_metrics_keys_as_is() -> Result<()> {
+        // Formatting would lowercase `Inter`, so it's only found as is
+        let metrics_json = &INTER_METRICS.replace(r#""inter":"#, r#""Inter":"#);
+        let font_metrics_map: FontMetricsMap = parse_json_with_source_context(metrics_json)?;
+        assert_eq!(find_missing_font("Inter", &font_metrics_map, false), None);
+        assert_eq!(
+            parse_font_metrics_lazily(metrics_json, "Inter")?,
+            font_metrics_map.0.get("Inter").cloned()
+        );
+
+        // The key as is wins over the formatted key, even in an earlier file
+        let formatted_entries = parse_metrics_key_entries(
+            &INTER_METRICS.replace(r#""ascent": 2728"#, r#""ascent": 2000"#),
+            "Inter",
+        )?;
+        let entry = formatted_entries
+            .or(parse_metrics_key_entries(metrics_json, "Inter")?)
+            .into_entry()
+            .context("Expected Inter")?;
+        assert_eq!(entry.ascent, 2728);
+        Ok(())
+    }
+
+    /// The options of a request for Inter at 400, 700 and 900, with 900 being
+    /// wider than the others
+    fn weighted_inter_options() -> Result<NextFontGoogleOptions> {
+        let mut options = request_options("Inter", r#"{"weight": ["400", "700", "900"]}"#)?;
+        options.weight_metrics_overrides = Some(BTreeMap::from([(
+            900,
+            FontMetricsOverride {
+                x_width_avg: Some(OrderedF64(1500.0)),
+                ..Default::default()
+            },
+        )]));
+        Ok(options)
+    }
+
+    #[test]
+    fn test_computes_adjustment_of_each_weight() -> Result<()> {
+        let options = weighted_inter_options()?;
+        let font_metrics = metrics_map(&[INTER_ENTRY]);
+        let metrics = &font_metrics.0["inter"];
+
+        let regular_adjustment = lookup_fallback(
+            "Inter",
+            metrics_map(&[INTER_ENTRY]),
+            &FallbackLookupOptions::default(),
+        )?
+        .adjustment;
+        assert_eq!(
+            lookup_weight_adjustment(&options, metrics.clone(), 400)?,
+            regular_adjustment
+        );
+        assert_eq!(
+            lookup_weight_adjustment(&options, metrics.clone(), 700)?,
+            regular_adjustment
+        );
+        assert_eq!(
+            lookup_weight_adjustment(&options, metrics.clone(), 900)?
+                .context("Expected an adjustment")?
+                .size_adjust,
+            (1500.0 / 2816.0) / (934.5116279069767 / 2048.0)
+        );
+        Ok(())
+    }
+
+    /// Metrics loaded into memory in advance, counting how often they're
+    /// loaded
+    struct CountingMetricsSource(FontMetricsMap, AtomicUsize);
+
+    #[async_trait]
+    impl MetricsSource for CountingMetricsSource {
+        async fn load(&self) -> Result<MetricsRef<'_>> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(&self.0))
+        }
+    }
+
+    #[test]
+    fn test_looks_up_metrics_once_per_weight_set() -> Result<()> {
+        let options = weighted_inter_options()?;
+        let font_metrics = metrics_map(&[INTER_ENTRY]);
+        let metrics_source = CountingMetricsSource(font_metrics.clone(), AtomicUsize::new(0));
+
+        let weight_adjustments = block_on(metrics_source.weight_font_adjustments(&options))?;
+        assert_eq!(metrics_source.1.load(Ordering::SeqCst), 1);
+        let metrics = &font_metrics.0["inter"];
+        assert_eq!(
+            weight_adjustments,
+            BTreeMap::from([
+                (
+                    400,
+                    lookup_weight_adjustment(&options, metrics.clone(), 400)?
+                ),
+                (
+                    700,
+                    lookup_weight_adjustment(&options, metrics.clone(), 700)?
+                ),
+                (
+                    900,
+                    lookup_weight_adjustment(&options, metrics.clone(), 900)?
+                ),
+            ])
+        );
+        assert_ne!(weight_adjustments[&400], weight_adjustments[&900]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_skips_font_family_suggestion_when_disabled() -> Result<()> {
+        let options = request_options(
+            "Intr",
+            r#"{"weight": "400", "disable_font_family_suggestions": true}"#,
+        )?;
+        let font_metrics = metrics_map(&[INTER_ENTRY]);
+
+        assert_eq!(
+            block_on(resolve_font_fallback(
+                &InMemoryMetricsSource(font_metrics),
+                &options,
+                &FontFamilyQuoting::AsNeeded,
+            ))?,
+            FallbackResolution {
+                fallback: ResolvedFallback::Error,
+                issues: vec![missing_metrics_issue("Intr", None)],
+            }
+        );
+        assert_eq!(
+            missing_metrics_issue("Intr", None).description,
+            "Skipping generating a fallback font."
+        );
+        Ok(())
+    }
+
+    /// Metrics loaded into memory in advance
+    struct InMemoryMetricsSource(pub FontMetricsMap);
+
+    #[async_trait]
+    impl MetricsSource for InMemoryMetricsSource {
+        async fn load(&self) -> Result<MetricsRef<'_>> {
+            Ok(Box::new(&self.0))
+        }
+    }
+
+    #[test]
+    fn test_resolves_fallback_from_in_memory_metrics() -> Result<()> {
+        let options = request_options("Inter", r#"{"weight": "400"}"#)?;
+        let metrics_source = InMemoryMetricsSource(metrics_map(&[INTER_ENTRY]));
+
+        let resolution = block_on(resolve_font_fallback(
+            &metrics_source,
+            &options,
+            &FontFamilyQuoting::AsNeeded,
+        ))?;
+        assert_eq!(resolution.issues, vec![]);
+        assert_eq!(
+            resolution.fallback,
+            ResolvedFallback::Automatic(lookup_fallback(
+                "Inter",
+                metrics_source.0.clone(),
+                &FallbackLookupOptions::default(),
+            )?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_for_nearest_optical_size() -> Result<()> {
+        let font_metrics = || -> Result<FontMetricsMap> {
+            parse_json_with_source_context(&inter_metrics_with_fields(
+                r#"
+                "styles": {
+                    "italic": {
+                        "xWidthAvg": 1300
+                    }
+                },
+                "defaultOpticalSize": 32,
+                "opticalSizes": {
+                    "14": {
+                        "ascent": 2728,
+                        "descent": -680,
+                        "lineGap": 0,
+                        "xWidthAvg": 1380
+                    },
+                    "72": {
+                        "ascent": 2600,
+                        "descent": -640,
+                        "lineGap": 0,
+                        "xWidthAvg": 1240
+                    }
+                }
+            "#,
+            ))
+        };
+        let size_adjust_for_style =
+            |optical_size: Option<f64>, style: Option<&str>| -> Result<f64> {
+                Ok(lookup_fallback(
+                    "Inter",
+                    font_metrics()?,
+                    &FallbackLookupOptions {
+                        optical_size,
+                        style: style.map(str::to_owned),
+                        ..Default::default()
+                    },
+                )?
+                .adjustment
+                .context("Expected an adjustment")?
+                .size_adjust)
+            };
+        let size_adjust = |optical_size: Option<f64>| size_adjust_for_style(optical_size, None);
+        let size_adjust_for_width =
+            |x_width_avg: f64| DEFAULT_SANS_SERIF_FONT.size_adjust_for(x_width_avg, 2816.0);
+
+        assert_eq!(size_adjust(None)?, INTER_ADJUSTMENT.size_adjust);
+        assert_eq!(size_adjust(Some(12.0))?, size_adjust_for_width(1380.0));
+        // The metrics at the default optical size are nearest
+        assert_eq!(size_adjust(Some(28.0))?, INTER_ADJUSTMENT.size_adjust);
+        assert_eq!(size_adjust(Some(60.0))?, size_adjust_for_width(1240.0));
+        assert_eq!(size_adjust(Some(96.0))?, size_adjust_for_width(1240.0));
+        // Style metrics apply at any optical size
+        assert_eq!(
+            size_adjust_for_style(Some(72.0), Some("italic"))?,
+            size_adjust_for_width(1300.0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_advance_width_ratio_is_default_size_adjust() {
+        let font_metrics = metrics_map(&[INTER_ENTRY]);
+        let inter = &font_metrics.0[&format_fallback_font_name("Inter")];
+
+        assert_eq!(
+            advance_width_ratio(inter, &DEFAULT_SANS_SERIF_FONT),
+            INTER_ADJUSTMENT.size_adjust
+        );
+        // Inter is wider than Arial
+        assert!(advance_width_ratio(inter, &DEFAULT_SANS_SERIF_FONT) > 1.0);
+    }
+
+    #[test]
+    fn test_size_adjust_for_other_default_fonts() -> Result<()> {
+        let font_metrics = metrics_map(&[("Inter", "sans-serif", 2728, -680, 0, 2816, 1335.0)]);
+
+        let arial_size_adjust =
+            size_adjust_for_default("Inter", &font_metrics, &DEFAULT_SANS_SERIF_FONT)?;
+        let times_size_adjust =
+            size_adjust_for_default("Inter", &font_metrics, &DEFAULT_SERIF_FONT)?;
+        assert_eq!(
+            arial_size_adjust,
+            lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?
+                .adjustment
+                .context("Expected an adjustment")?
+                .size_adjust
+        );
+        assert_eq!(
+            times_size_adjust,
+            (1335.0 / 2816.0) / (854.3953488372093 / 2048.0)
+        );
+        // Times New Roman is narrower than Arial, so it's scaled up more
+        assert!(times_size_adjust > arial_size_adjust);
+        Ok(())
+    }
+
+    #[test]
+    fn test_times_fallback_resolution() -> Result<()> {
+        let options = request_options("Inter", r#"{"weight": "400"}"#)?;
+        let metrics_source = InMemoryMetricsSource(metrics_map(&[(
+            "Inter",
+            "sans-serif",
+            2728,
+            -680,
+            0,
+            2816,
+            1335.0,
+        )]));
+
+        let (resolution, duration) = block_on(timed(resolve_font_fallback(
+            &metrics_source,
+            &options,
+            &FontFamilyQuoting::AsNeeded,
+        )));
+        assert!(matches!(
+            resolution?.fallback,
+            ResolvedFallback::Automatic(_)
+        ));
+        assert!(duration > Duration::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_formats_any_font_family_without_panicking() {
+        let fragments = [
+            "", " ", "a", "Z", "1", "_", "-", "ß", "İ", "日本", "\u{301}", "\t", "😀",
+        ];
+        for first in fragments {
+            for second in fragments {
+                for third in fragments {
+                    let font_family = format!("{}{}{}", first, second, third);
+                    let formatted = format_fallback_font_name(&font_family);
+                    assert!(
+                        !formatted.chars().any(char::is_whitespace),
+                        "{:?} formatted as {:?}",
+                        font_family,
+                        formatted
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_preferred_fallback_overrides_category_default() -> Result<()> {
+        let font_metrics = |preferred_fallback: &str| -> Result<FontMetricsMap> {
+            parse_json_with_source_context(&format!(
+                r#"
+                {{
+                    "lora": {{
+                        "familyName": "Lora",
+                        "category": "serif",
+                        "capHeight": 700,
+                        "ascent": 1006,
+                        "descent": -274,
+                        "lineGap": 0,
+                        "unitsPerEm": 1000,
+                        "xHeight": 500,
+                        "xWidthAvg": 468,
+                        "preferredFallback": "{}"
+                    }}
+                }}
+            "#,
+                preferred_fallback
+            ))
+        };
+
+        assert_eq!(
+            lookup_fallback(
+                "Lora",
+                font_metrics("Arial")?,
+                &FallbackLookupOptions::default()
+            )?
+            .font_family,
+            "Arial"
+        );
+        // Fonts without bundled metrics can't be adjusted, so the category's
+        // default is kept
+        assert_eq!(
+            lookup_fallback(
+                "Lora",
+                font_metrics("Helvetica")?,
+                &FallbackLookupOptions::default()
+            )?
+            .font_family,
+            "Times New Roman"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_keeps_font_version() -> Result<()> {
+        let font_metrics =
+            parse_json_with_source_context(&inter_metrics_with_fields(r#""version": "3.019""#))?;
+
+        assert_eq!(
+            lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?
+                .font_version
+                .as_deref(),
+            Some("3.019")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_treats_empty_fallback_list_as_omitted() -> Result<()> {
+        let options = request_options("Inter", r#"{"weight": "400", "fallback": []}"#)?;
+        let metrics_source = InMemoryMetricsSource(metrics_map(&[INTER_ENTRY]));
+
+        let resolution = block_on(resolve_font_fallback(
+            &metrics_source,
+            &options,
+            &FontFamilyQuoting::AsNeeded,
+        ))?;
+        assert!(matches!(
+            resolution.fallback,
+            ResolvedFallback::Automatic(Fallback { ref font_family, .. }) if font_family == "Arial"
+        ));
+        assert_eq!(
+            resolution
+                .issues
+                .iter()
+                .map(|issue| (issue.title.as_str(), &issue.severity))
+                .collect::<Vec<_>>(),
+            vec![(
+                "Empty fallback list for font `Inter`",
+                &IssueSeverity::Warning
+            )]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_consolidates_weights_into_one_fallback() -> Result<()> {
+        let mut options = request_options(
+            "Inter",
+            r#"
+            {
+                "weight": ["700", "400"],
+                "consolidate_fallback_weights": true
+            }
+        "#,
+        )?;
+        options.weight_metrics_overrides = Some(BTreeMap::from([(
+            700,
+            FontMetricsOverride {
+                x_width_avg: Some(OrderedF64(1500.0)),
+                ..Default::default()
+            },
+        )]));
+        let font_metrics = || metrics_map(&[INTER_ENTRY]);
+
+        assert_eq!(get_consolidated_weight_range(&options), Some((400, 700)));
+        let regular_size_adjust = 1.0389481114147647;
+        let bold_size_adjust = (1500.0 / 2816.0) / (934.5116279069767 / 2048.0);
+        let ResolvedFallback::Automatic(fallback) = block_on(resolve_font_fallback(
+            &InMemoryMetricsSource(font_metrics()),
+            &options,
+            &FontFamilyQuoting::AsNeeded,
+        ))?
+        .fallback else {
+            panic!("Expected an automatic fallback");
+        };
+        assert_eq!(
+            fallback
+                .adjustment
+                .context("Expected an adjustment")?
+                .size_adjust,
+            (regular_size_adjust + bold_size_adjust) / 2.0
+        );
+
+        options.consolidate_fallback_weights = false;
+        assert_eq!(get_consolidated_weight_range(&options), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_warns_about_inflated_metrics() -> Result<()> {
+        let font_metrics = metrics_map(&[("Inter", "sans-serif", 27280, -6800, 0, 2816, 1335.0)]);
+
+        let fallback = lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?;
+        assert_eq!(
+            fallback.warnings,
+            vec![FallbackWarning {
+                title: "Font metrics for `Inter` look inflated".to_owned(),
+                description: "The ascent (27280) and descent (-6800) span more than 2 times the \
+                              units per em (2816), so the metrics are likely wrong and the \
+                              fallback may not match the font."
+                    .to_owned(),
+            }]
+        );
+
+        let font_metrics = metrics_map(&[INTER_ENTRY]);
+        assert_eq!(
+            lookup_fallback("Inter", font_metrics, &FallbackLookupOptions::default())?.warnings,
+            vec![]
+        );
+        Ok(())
+    }
+
+    /// Returns the next version of the metrics on every load, like the
+    /// bundled metrics files changing between two computations of a fallback
+    struct ReloadingMetricsSource(Mutex<VecDeque<FontMetricsMap>>);
+
+    #[async_trait]
+    impl MetricsSource for ReloadingMetricsSource {
+        async fn load(&self) -> Result<MetricsRef<'_>> {
+            let font_metrics_map = self
+                .0
+                .lock()
+                .unwrap()
+                .pop_front()
+                .context("Expected another version of the metrics")?;
+            Ok(Box::new(Arc::new(font_metrics_map)))
+        }
+    }
+
+    #[test]
+    fn test_resolves_with_reloaded_metrics() -> Result<()> {
+        let options = request_options("Inter", r#"{"weight": "400"}"#)?;
+        let metrics_source = ReloadingMetricsSource(Mutex::new(VecDeque::from([
+            metrics_map(&[("Intern", "sans-serif", 2728, -680, 0, 2816, 1335.0)]),
+            metrics_map(&[INTER_ENTRY]),
+            metrics_map(&[("Inter", "sans-serif", 2728, -680, 0, 2816, 1500.0)]),
+        ])));
+        let resolve = || {
+            block_on(resolve_font_fallback(
+                &metrics_source,
+                &options,
+                &FontFamilyQuoting::AsNeeded,
+            ))
+        };
+        let size_adjust = |resolution: FallbackResolution| -> Result<f64> {
+            let ResolvedFallback::Automatic(fallback) = resolution.fallback else {
+                bail!("Expected an automatic fallback");
+            };
+            Ok(fallback
+                .adjustment
+                .context("Expected an adjustment")?
+                .size_adjust)
+        };
+
+        // Neither the missing font nor its adjustment are kept once the
+        // metrics change
+        assert_eq!(resolve()?.fallback, ResolvedFallback::Error);
+        assert_eq!(size_adjust(resolve()?)?, INTER_ADJUSTMENT.size_adjust);
+        assert_eq!(
+            size_adjust(resolve()?)?,
+            DEFAULT_SANS_SERIF_FONT.size_adjust_for(1500.0, 2816.0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_warns_about_misspelled_manual_fallback() -> Result<()> {
+        let options = request_options(
+            "Inter",
+            r#"
+            {
+                "weight": "400",
+                "fallback": ["Robotto", "'Segoe UI'", "Arial", "system-ui", "sans-serif"],
+                "validate_manual_fallback": true
+            }
+        "#,
+        )?;
+        let metrics_source = InMemoryMetricsSource(metrics_map(&[
+            INTER_ENTRY,
+            ("Roboto", "sans-serif", 1900, -500, 0, 2048, 911.0),
+        ]));
+
+        let resolution = block_on(resolve_font_fallback(
+            &metrics_source,
+            &options,
+            &FontFamilyQuoting::AsNeeded,
+        ))?;
+        assert_eq!(
+            resolution.fallback,
+            ResolvedFallback::Manual(
+                ["Robotto", "'Segoe UI'", "Arial", "system-ui", "sans-serif"]
+                    .map(str::to_owned)
+                    .to_vec()
+            )
+        );
+        assert_eq!(
+            resolution.issues,
+            vec![FallbackIssue {
+                title: "Unrecognized font `Robotto` in the fallback of font `Inter`".to_owned(),
+                description: "It is neither a generic font family, a common system font nor a \
+                              Google font, so it may be misspelled. The fallback is used as \
+                              given. Did you mean `Roboto`?"
+                    .to_owned(),
+                severity: IssueSeverity::Warning,
+                cause: None,
+            }]
+        );
+
+        let font_metrics = metrics_map(&[("Roboto", "sans-serif", 1900, -500, 0, 2048, 911.0)]);
+        assert_eq!(
+            find_unrecognized_fallback_fonts(&["\"Roboto\"".to_owned()], &font_metrics),
+            Vec::<&str>::new()
+        );
+        Ok(())
+    }
+
+    /// Resolves fallbacks through [[get_font_fallback]] with the metrics read
+    /// from a project on disk, checking the filesystem and turbo-tasks wiring
+    /// rather than the math of the other tests.
+    #[tokio::test]
+    async fn test_gets_font_fallback_from_project_metrics() -> Result<()> {
+        run_in_project(INTER_METRICS, |context| async move {
+            let get_fallback = |font_family: &str| -> Result<_> {
+                Ok(get_font_fallback(
+                    context,
+                    font_options(font_family, r#"{"weight": "400"}"#)?,
+                    U32Vc::cell(0xc123b8),
+                ))
+            };
+
+            let inter = get_fallback("Inter")?;
+            let inter_fallback = inter.strongly_consistent().await?;
+            let FontFallback::Automatic(fallback) = &*inter_fallback else {
+                bail!("Expected an automatic fallback");
+            };
+            let fallback = fallback.await?;
+            assert_eq!(
+                *fallback.scoped_font_family.await?,
+                format_scoped_font_family(&FontFamilyType::Fallback, "Inter", 0xc123b8)
+            );
+            assert_eq!(*fallback.local_font_family.await?, "Arial");
+            assert_eq!(fallback.adjustment, Some(INTER_ADJUSTMENT));
+            assert_eq!(issue_titles(inter).await?, Vec::<String>::new());
+
+            let roboto = get_fallback("Roboto")?;
+            assert!(matches!(
+                *roboto.strongly_consistent().await?,
+                FontFallback::Error
+            ));
+            assert_eq!(
+                issue_titles(roboto).await?,
+                vec!["Failed to find font override values for font `Roboto`".to_owned()]
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_gets_font_metrics_from_its_entry_only() -> Result<()> {
+        run_in_project(INTER_METRICS, |context| async move {
+            let font_metrics_map: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+            let get_metrics = |font_family: &str| -> Result<_> {
+                Ok(get_font_metrics(
+                    context,
+                    font_options(font_family, r#"{"weight": "400"}"#)?,
+                ))
+            };
+
+            let Some(inter) = &*get_metrics("Inter")?.await? else {
+                bail!("Expected metrics for Inter");
+            };
+            assert_eq!(*inter.await?, font_metrics_map.0["inter"]);
+            assert!(get_metrics("Roboto")?.await?.is_none());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_gets_adjustment_of_each_weight_from_project_metrics() -> Result<()> {
+        run_in_project(INTER_METRICS, |context| async move {
+            let options = weighted_inter_options()?;
+            let font_metrics: FontMetricsMap = parse_json_with_source_context(INTER_METRICS)?;
+            let weight_adjustments = get_weight_font_adjustments(
+                context,
+                NextFontGoogleOptionsVc::new(Value::new(options.clone())),
+            )
+            .await?;
+
+            assert_eq!(
+                weight_adjustments.keys().copied().collect::<Vec<_>>(),
+                vec![400, 700, 900]
+            );
+            for (weight, adjustment) in weight_adjustments.iter() {
+                assert_eq!(
+                    *adjustment,
+                    lookup_weight_adjustment(&options, font_metrics.0["inter"].clone(), *weight)?
+                );
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_looks_up_missing_font_once_until_metrics_change() -> Result<()> {
+        let (project_dir, project_path) = create_project(
+            r#"
+            {
+                "roboto": {
+                    "familyName": "Roboto",
+                    "category": "sans-serif",
+                    "capHeight": 1456,
+                    "ascent": 1900,
+                    "descent": -500,
+                    "lineGap": 0,
+                    "unitsPerEm": 2048,
+                    "xHeight": 1082,
+                    "xWidthAvg": 911
+                }
+            }
+        "#,
+        )?;
+
+        run_once(async move {
+            let file_system = DiskFileSystemVc::new("project".to_owned(), project_path);
+            let context = file_system.as_file_system().root();
+            let missing_font = || get_missing_font(context, "Robto".to_owned(), true);
+
+            // A repeated miss is served by the task of the first one,
+            // including its suggestion
+            let first_miss = missing_font().resolve().await?;
+            assert_eq!(missing_font().resolve().await?, first_miss);
+            assert_eq!(
+                *first_miss.strongly_consistent().await?,
+                Some(MissingFont {
+                    suggestion: Some("Roboto".to_owned())
+                })
+            );
+
+            write_font_metrics(&project_dir, "{}")?;
+            file_system.await?.invalidate();
+            assert_eq!(
+                *missing_font().strongly_consistent().await?,
+                Some(MissingFont { suggestion: None })
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_recomputes_font_fallback_when_metrics_change() -> Result<()> {
+        let (project_dir, project_path) = create_project(INTER_METRICS)?;
+
+        run_once(async move {
+            let file_system = DiskFileSystemVc::new("project".to_owned(), project_path);
+            let context = file_system.as_file_system().root();
+            let fallback = get_font_fallback(
+                context,
+                font_options("Inter", r#"{"weight": "400"}"#)?,
+                U32Vc::cell(0xc123b8),
+            );
+            let adjustment = || async move {
+                let FontFallback::Automatic(fallback) = &*fallback.strongly_consistent().await?
+                else {
+                    bail!("Expected an automatic fallback");
+                };
+                Ok(fallback.await?.adjustment)
+            };
+            assert_eq!(adjustment().await?, Some(INTER_ADJUSTMENT));
+
+            // Inter twice as wide, as if the metrics were regenerated while
+            // watching the project
+            write_font_metrics(
+                &project_dir,
+                &INTER_METRICS.replace(r#""xWidthAvg": 1335"#, r#""xWidthAvg": 2670"#),
+            )?;
+            file_system.await?.invalidate();
+            let rewritten_metrics =
+                metrics_map(&[("Inter", "sans-serif", 2728, -680, 0, 2816, 2670.0)]);
+            assert_eq!(
+                adjustment().await?,
+                lookup_fallback(
+                    "Inter",
+                    rewritten_metrics,
+                    &FallbackLookupOptions::default()
+                )?
+                .adjustment
+            );
+            assert_ne!(adjustment().await?, Some(INTER_ADJUSTMENT));
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_lists_generic_only_fallback_without_font_face() -> Result<()> {
+        run_in_project(INTER_METRICS, |context| async move {
+            let fallback = get_font_fallback(
+                context,
+                font_options(
+                    "Inter",
+                    r#"{"weight": "400", "fallback_generic_only": true}"#,
+                )?,
+                U32Vc::cell(0xc123b8),
+            )
+            .strongly_consistent()
+            .await?;
+            let FontFallback::Manual(fonts) = &*fallback else {
+                bail!("Expected a manual fallback");
+            };
+            assert_eq!(*fonts.await?, vec!["sans-serif".to_owned()]);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_adjusts_fallback_preset_against_its_first_known_font() -> Result<()> {
+        run_in_project(INTER_METRICS, |context| async move {
+            let get_fallback = |preset: &str| -> Result<_> {
+                Ok(get_font_fallback(
+                    context,
+                    font_options(
+                        "Inter",
+                        &format!(r#"{{"weight": "400", "fallback_preset": "{}"}}"#, preset),
+                    )?,
+                    U32Vc::cell(0xc123b8),
+                ))
+            };
+
+            let sans = get_fallback("tailwindSans")?.strongly_consistent().await?;
+            let FontFallback::Automatic(fallback) = &*sans else {
+                bail!("Expected an automatic fallback");
+            };
+            let fallback = fallback.await?;
+            assert_eq!(*fallback.local_font_family.await?, "Arial");
+            assert_eq!(fallback.adjustment, Some(INTER_ADJUSTMENT));
+            assert_eq!(
+                fallback.font_stack,
+                FallbackPreset::TailwindSans.font_families()
+            );
+
+            // Inter is sans-serif, but the serif stack starts with Times New Roman
+            let serif = get_fallback("tailwindSerif")?.strongly_consistent().await?;
+            let FontFallback::Automatic(fallback) = &*serif else {
+                bail!("Expected an automatic fallback");
+            };
+            let fallback = fallback.await?;
+            assert_eq!(*fallback.local_font_family.await?, "Times New Roman");
+            assert_eq!(
+                fallback.adjustment.map(|adjustment| adjustment.size_adjust),
+                Some(DEFAULT_SERIF_FONT.size_adjust_for(1335.0, 2816.0))
+            );
+
+            // None of the monospace stack has known metrics
+            let mono = get_fallback("tailwindMono")?.strongly_consistent().await?;
+            let FontFallback::Manual(fonts) = &*mono else {
+                bail!("Expected a manual fallback");
+            };
+            assert_eq!(*fonts.await?, FallbackPreset::TailwindMono.font_families());
+            Ok(())
+        })
+        .await
+    }
+
+    /// The JSON description of the fallback preview issue emitted by resolving
+    /// `fallback`
+    async fn fallback_preview(fallback: FontFallbackVc) -> Result<serde_json::Value> {
+        for issue in IssueVc::peek_issues_with_path(fallback)
+            .await?
+            .strongly_consistent()
+            .await?
+            .iter()
+        {
+            if issue
+                .title()
+                .await?
+                .starts_with("Fallback preview for font")
+            {
+                return Ok(serde_json::from_str(&issue.description().await?)?);
+            }
+        }
+
+        bail!("Expected a fallback preview issue")
+    }
+
+    #[tokio::test]
+    async fn test_reports_fallback_preview_when_enabled() -> Result<()> {
+        run_in_project(INTER_METRICS, |context| async move {
+            let get_fallback = |arguments: &str| -> Result<_> {
+                Ok(get_font_fallback(
+                    context,
+                    font_options("Inter", arguments)?,
+                    U32Vc::cell(0xc123b8),
+                ))
+            };
+
+            let fallback = get_fallback(r#"{"weight": "400"}"#)?;
+            assert_eq!(issue_titles(fallback).await?, Vec::<String>::new());
+
+            let fallback = get_fallback(r#"{"weight": "400", "fallback_preview": true}"#)?;
+            assert_eq!(
+                issue_titles(fallback).await?,
+                vec!["Fallback preview for font `Inter`".to_owned()]
+            );
+            let preview = fallback_preview(fallback).await?;
+            assert_eq!(
+                preview["contentHash"],
+                format!("{:016x}", *fallback.content_hash().await?)
+            );
+            assert_eq!(preview["nearestCandidate"], "Arial");
+            assert_eq!(
+                preview["candidates"][0]["sizeAdjust"],
+                INTER_ADJUSTMENT.size_adjust
+            );
+            // The adjustment is in place, so enabling it adds nothing more
+            assert!(preview.get("adjustmentImpact").is_none());
+            let mut steps = vec![];
+            let mut node = &preview["decisionTree"];
+            while !node.is_null() {
+                steps.push(format!(
+                    "{}: {}",
+                    node["step"].as_str().unwrap_or_default(),
+                    node["outcome"].as_str().unwrap_or_default()
+                ));
+                node = &node["children"][0];
+            }
+            assert_eq!(
+                steps[..5],
+                [
+                    "input: Inter",
+                    "formatting: inter",
+                    "lookup: Inter",
+                    "category: sans-serif",
+                    "defaultFont: Arial"
+                ]
+            );
+            assert!(steps[5].starts_with("adjustment: ascent-override: 93.24%;"));
+
+            let fallback = get_fallback(
+                r#"{"weight": "400", "adjust_font_fallback": false, "fallback_preview": true}"#,
+            )?;
+            let preview = fallback_preview(fallback).await?;
+            assert!(preview["adjustment"].is_null());
+            assert_eq!(
+                preview["adjustmentImpact"]["sizeAdjust"],
+                INTER_ADJUSTMENT.size_adjust
+            );
+            assert_eq!(
+                preview["adjustmentImpact"]["ascent"],
+                INTER_ADJUSTMENT.ascent
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_hashes_resolved_fallback_content() -> Result<()> {
+        run_in_project(INTER_METRICS, |context| async move {
+            let content_hash = |arguments: &'static str| async move {
+                Ok::<_, anyhow::Error>(
+                    *get_font_fallback(
+                        context,
+                        font_options("Inter", arguments)?,
+                        U32Vc::cell(0xc123b8),
+                    )
+                    .content_hash()
+                    .await?,
+                )
+            };
+
+            // The preview doesn't change the fallback resolved
+            assert_eq!(
+                content_hash(r#"{"weight": "400"}"#).await?,
+                content_hash(r#"{"weight": "400", "fallback_preview": true}"#).await?
+            );
+            assert_ne!(
+                content_hash(r#"{"weight": "400"}"#).await?,
+                content_hash(r#"{"weight": "400", "adjust_font_fallback": false}"#).await?
+            );
+            Ok(())
+        })
+        .await
     }
 }
@@ -7,10 +7,14 @@ use turbo_binding::{
             primitives::{OptionStringVc, StringVc, U32Vc},
             Value,
         },
-        tasks_fs::{json::parse_json_with_source_context, FileContent, FileSystemPathVc},
+        tasks_fs::{
+            json::parse_json_with_source_context, FileContent, FileContentVc, FileSystemPathVc,
+        },
     },
     turbopack::{
         core::{
+            asset::{Asset, AssetContentVc},
+            ident::AssetIdentVc,
             resolve::{
                 options::{
                     ImportMapResult, ImportMapResultVc, ImportMapping, ImportMappingReplacement,
@@ -20,23 +24,23 @@ use turbo_binding::{
                 pattern::QueryMapVc,
                 ResolveResult,
             },
-            virtual_asset::VirtualAssetVc,
         },
         node::execution_context::ExecutionContextVc,
     },
 };
 
 use self::{
-    font_fallback::get_font_fallback,
+    font_fallback::{get_font_fallback, get_generic_font_family},
     options::{options_from_request, FontDataEntry, FontWeights, NextFontGoogleOptionsVc},
     stylesheet::build_stylesheet,
     util::{get_font_axes, get_stylesheet_url},
 };
 use super::{
     font_fallback::{FontFallback, FontFallbackVc},
+    stylesheet::CssInJsFallback,
     util::{
-        get_request_hash, get_request_id, get_scoped_font_family, FontCssProperties,
-        FontCssPropertiesVc, FontFamilyType,
+        append_generic_font_family, get_request_hash, get_request_id, get_scoped_font_family,
+        quote_font_family, FontCssProperties, FontCssPropertiesVc, FontFamilyType,
     },
 };
 use crate::{embed_js::next_js_file_path, util::load_next_json};
@@ -94,49 +98,20 @@ impl ImportMappingReplacement for NextFontGoogleReplacer {
         let query = &*query_vc.await?;
         let options = font_options_from_query_map(*query_vc, font_data);
         let request_hash = get_request_hash(*query_vc);
-        let fallback = get_font_fallback(self.project_path, options, request_hash);
-        let properties = get_font_css_properties(options, fallback, request_hash).await?;
-        let js_asset = VirtualAssetVc::new(
-                next_js_file_path("internal/font/google")
-                    .join(&format!("{}.js", get_request_id(options.font_family(), request_hash).await?)),
-                FileContent::Content(
-                    formatdoc!(
-                        r#"
-                            import cssModule from "@vercel/turbopack-next/internal/font/google/cssmodule.module.css?{}";
-                            const fontData = {{
-                                className: cssModule.className,
-                                style: {{
-                                    fontFamily: "{}",
-                                    {}{}
-                                }},
-                            }};
-
-                            if (cssModule.variable != null) {{
-                                fontData.variable = cssModule.variable;
-                            }}
-
-                            export default fontData;
-                        "#,
-                        // Pass along whichever options we received to the css handler
-                        qstring::QString::new(query.as_ref().unwrap().iter().collect()),
-                        properties.font_family.await?,
-                        properties
-                            .weight
-                            .await?
-                            .as_ref()
-                            .map(|w| format!("fontWeight: {},\n", w))
-                            .unwrap_or_else(|| "".to_owned()),
-                        properties
-                            .style
-                            .await?
-                            .as_ref()
-                            .map(|s| format!("fontStyle: \"{}\",\n", s))
-                            .unwrap_or_else(|| "".to_owned()),
-                    )
-                    .into(),
-                )
-                .into(),
-            );
+        let js_asset = NextFontGoogleJsAsset {
+            path: next_js_file_path("internal/font/google").join(&format!(
+                "{}.js",
+                get_request_id(options.font_family(), request_hash).await?
+            )),
+            project_path: self.project_path,
+            // Pass along whichever options we received to the css handler
+            query: StringVc::cell(
+                qstring::QString::new(query.as_ref().unwrap().iter().collect()).to_string(),
+            ),
+            options,
+            request_hash,
+        }
+        .cell();
 
         Ok(ImportMapResult::Result(ResolveResult::asset(js_asset.into()).into()).into())
     }
@@ -249,25 +224,82 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
             None => None,
         };
 
-        let font_fallback = get_font_fallback(self.project_path, options, request_hash);
-        let css_asset = VirtualAssetVc::new(
-            css_virtual_path,
-            FileContent::Content(
-                build_stylesheet(
-                    OptionStringVc::cell(stylesheet),
-                    get_font_css_properties(options, font_fallback, request_hash),
-                    font_fallback,
-                )
-                .await?
-                .into(),
-            )
-            .into(),
-        );
+        let css_asset = NextFontGoogleCssModuleAsset {
+            path: css_virtual_path,
+            project_path: self.project_path,
+            stylesheet: OptionStringVc::cell(stylesheet),
+            options,
+            request_hash,
+        }
+        .cell();
 
         Ok(ImportMapResult::Result(ResolveResult::asset(css_asset.into()).into()).into())
     }
 }
 
+/// The JavaScript module of a font, exporting its class name and styles. Its
+/// content, and with it the font's fallback, is only computed once read, i.e.
+/// not for fonts whose modules are never consumed.
+#[turbo_tasks::value(shared)]
+struct NextFontGoogleJsAsset {
+    path: FileSystemPathVc,
+    project_path: FileSystemPathVc,
+    /// The query of the request, passed along to the CSS module
+    query: StringVc,
+    options: NextFontGoogleOptionsVc,
+    request_hash: U32Vc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for NextFontGoogleJsAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        AssetIdentVc::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        get_js_content(
+            self.project_path,
+            self.query,
+            self.options,
+            self.request_hash,
+        )
+        .into()
+    }
+}
+
+/// The CSS module of a font, with its `@font-face` definitions. Like
+/// [[NextFontGoogleJsAsset]], its content is only computed once read.
+#[turbo_tasks::value(shared)]
+struct NextFontGoogleCssModuleAsset {
+    path: FileSystemPathVc,
+    project_path: FileSystemPathVc,
+    /// The stylesheet from Google Fonts, if it could be fetched
+    stylesheet: OptionStringVc,
+    options: NextFontGoogleOptionsVc,
+    request_hash: U32Vc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for NextFontGoogleCssModuleAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        AssetIdentVc::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        get_stylesheet_content(
+            self.project_path,
+            self.stylesheet,
+            self.options,
+            self.request_hash,
+        )
+        .into()
+    }
+}
+
 #[turbo_tasks::function]
 async fn load_font_data(project_root: FileSystemPathVc) -> Result<FontDataVc> {
     let data: FontData = load_next_json(
@@ -279,6 +311,106 @@ async fn load_font_data(project_root: FileSystemPathVc) -> Result<FontDataVc> {
     Ok(data.cell())
 }
 
+/// Builds the content of a font's JavaScript module, once the content of its
+/// [[NextFontGoogleJsAsset]] is read.
+#[turbo_tasks::function]
+async fn get_js_content(
+    project_path: FileSystemPathVc,
+    query: StringVc,
+    options: NextFontGoogleOptionsVc,
+    request_hash: U32Vc,
+) -> Result<FileContentVc> {
+    let font_fallback = get_font_fallback(project_path, options, request_hash);
+    let properties = get_font_css_properties(
+        options,
+        font_fallback,
+        get_generic_font_family(project_path, options),
+        request_hash,
+    )
+    .await?;
+    let css_in_js_fallback = match &*font_fallback.await? {
+        FontFallback::Automatic(fallback) if options.await?.fallback_css_in_js => {
+            let fallback = fallback.await?;
+            Some(serde_json::to_string(&CssInJsFallback::new(
+                &fallback.scoped_font_family.await?,
+                fallback.adjustment.as_ref(),
+                options.await?.fallback_descent_sign,
+                &*options.quoting().await?,
+            ))?)
+        }
+        _ => None,
+    };
+
+    Ok(FileContent::Content(
+        formatdoc!(
+            r#"
+                import cssModule from "@vercel/turbopack-next/internal/font/google/cssmodule.module.css?{}";
+                const fontData = {{
+                    className: cssModule.className,
+                    style: {{
+                        fontFamily: "{}",
+                        {}{}
+                    }},
+                }};
+
+                if (cssModule.variable != null) {{
+                    fontData.variable = cssModule.variable;
+                }}
+
+                {}export default fontData;
+            "#,
+            query.await?,
+            properties.font_family.await?,
+            properties
+                .weight
+                .await?
+                .as_ref()
+                .map(|w| format!("fontWeight: {},\n", w))
+                .unwrap_or_else(|| "".to_owned()),
+            properties
+                .style
+                .await?
+                .as_ref()
+                .map(|s| format!("fontStyle: \"{}\",\n", s))
+                .unwrap_or_else(|| "".to_owned()),
+            css_in_js_fallback
+                .map(|fallback| format!("fontData.fallback = {};\n\n", fallback))
+                .unwrap_or_default(),
+        )
+        .into(),
+    )
+    .into())
+}
+
+/// Builds the content of a font's CSS module, once the content of its
+/// [[NextFontGoogleCssModuleAsset]] is read.
+#[turbo_tasks::function]
+async fn get_stylesheet_content(
+    project_path: FileSystemPathVc,
+    stylesheet: OptionStringVc,
+    options: NextFontGoogleOptionsVc,
+    request_hash: U32Vc,
+) -> Result<FileContentVc> {
+    let font_fallback = get_font_fallback(project_path, options, request_hash);
+    Ok(FileContent::Content(
+        build_stylesheet(
+            project_path,
+            stylesheet,
+            get_font_css_properties(
+                options,
+                font_fallback,
+                get_generic_font_family(project_path, options),
+                request_hash,
+            ),
+            font_fallback,
+            options,
+        )
+        .await?
+        .into(),
+    )
+    .into())
+}
+
 /// Updates references to the unscoped font family from Google to use scoped
 /// font family names.
 #[turbo_tasks::function]
@@ -287,11 +419,15 @@ async fn update_google_stylesheet(
     options: NextFontGoogleOptionsVc,
     scoped_font_family: StringVc,
 ) -> Result<StringVc> {
-    // Update font-family definitions to the scoped name
+    // Update font-family definitions to the scoped name. Google always quotes
+    // the family name.
     // TODO: Do this more resiliently, e.g. transforming an swc ast
     Ok(StringVc::cell(stylesheet.await?.replace(
         &format!("font-family: '{}';", &*options.await?.font_family),
-        &format!("font-family: '{}';", &*scoped_font_family.await?),
+        &format!(
+            "font-family: {};",
+            quote_font_family(&scoped_font_family.await?, &*options.quoting().await?)
+        ),
     )))
 }
 
@@ -331,6 +467,7 @@ async fn get_stylesheet_url_from_options(
 async fn get_font_css_properties(
     options_vc: NextFontGoogleOptionsVc,
     font_fallback: FontFallbackVc,
+    generic_font_family: StringVc,
     request_hash: U32Vc,
 ) -> Result<FontCssPropertiesVc> {
     let options = &*options_vc.await?;
@@ -341,7 +478,8 @@ async fn get_font_css_properties(
     )
     .await?;
 
-    let mut font_families = vec![format!("'{}'", scoped_font_family.clone())];
+    let quoting = &*options_vc.quoting().await?;
+    let mut font_families = vec![quote_font_family(scoped_font_family, quoting)];
     let font_fallback = &*font_fallback.await?;
     match font_fallback {
         FontFallback::Manual(fonts) => {
@@ -349,9 +487,16 @@ async fn get_font_css_properties(
         }
         FontFallback::Automatic(fallback) => {
             let fallback = &*fallback.await?;
-            font_families.push(format!("'{}'", *fallback.scoped_font_family.await?));
+            font_families.push(quote_font_family(
+                &fallback.scoped_font_family.await?,
+                quoting,
+            ));
+            font_families.extend(fallback.font_stack.iter().cloned());
         }
-        FontFallback::Error => {}
+        FontFallback::Error | FontFallback::Skipped => {}
+    }
+    if options.append_generic_font_family {
+        append_generic_font_family(&mut font_families, &generic_font_family.await?);
     }
 
     Ok(FontCssPropertiesVc::cell(FontCssProperties {
@@ -361,6 +506,7 @@ async fn get_font_css_properties(
             FontWeights::Fixed(weights) => weights.first().map(|w| w.to_string()),
         }),
         style: OptionStringVc::cell(options.styles.first().cloned()),
+        font_feature_settings: OptionStringVc::cell(options.font_feature_settings.clone()),
         variable: OptionStringVc::cell(options.variable.clone()),
     }))
 }
@@ -404,7 +550,7 @@ async fn get_mock_stylesheet(
             tasks_fs::{DiskFileSystemVc, File, FileSystem},
         },
         turbopack::{
-            core::{context::AssetContext, ident::AssetIdentVc},
+            core::{context::AssetContext, virtual_asset::VirtualAssetVc},
             ecmascript::{
                 EcmascriptInputTransformsVc, EcmascriptModuleAssetType, EcmascriptModuleAssetVc,
             },
@@ -485,3 +631,453 @@ async fn get_mock_stylesheet(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, future::Future};
+
+    use anyhow::{bail, Context, Result};
+    use tempfile::TempDir;
+    use turbo_binding::{
+        turbo::{
+            tasks_fs::{
+                json::parse_json_with_source_context, DiskFileSystemVc, FileContent, FileSystem,
+                FileSystemPathVc,
+            },
+            tasks_memory::MemoryBackend,
+        },
+        turbopack::{
+            core::{
+                asset::{Asset, AssetContent, AssetVc},
+                issue::{Issue, IssueSeverity, IssueVc},
+            },
+            dev_server::source::{ContentSourceContent, ContentSourceContentVc},
+        },
+    };
+    use turbo_tasks::{
+        primitives::{OptionStringVc, StringVc, U32Vc},
+        CollectiblesSource, TryJoinIterExt, TurboTasks, Value,
+    };
+
+    use super::{
+        options::{options_from_request, FontData, NextFontGoogleOptions, NextFontGoogleOptionsVc},
+        request::NextFontRequest,
+        NextFontGoogleCssModuleAsset, NextFontGoogleJsAsset,
+    };
+    use crate::next_font::issue::NextFontCheckProcessorVc;
+
+    /// Capsize metrics of Inter, as bundled with Next.js
+    pub(super) const INTER_METRICS: &str = r#"
+        {
+            "inter": {
+                "familyName": "Inter",
+                "category": "sans-serif",
+                "capHeight": 2048,
+                "ascent": 2728,
+                "descent": -680,
+                "lineGap": 0,
+                "unitsPerEm": 2816,
+                "xHeight": 1536,
+                "xWidthAvg": 1335
+            }
+        }
+    "#;
+
+    /// Creates a project with `font_metrics` as Next.js' bundled font metrics,
+    /// returning it along with its path
+    pub(super) fn create_project(font_metrics: &str) -> Result<(TempDir, String)> {
+        let project_dir = tempfile::tempdir()?;
+        let next_dir = project_dir.path().join("node_modules/next");
+        fs::create_dir_all(next_dir.join("dist/server"))?;
+        fs::write(next_dir.join("package.json"), r#"{"name": "next"}"#)?;
+        write_font_metrics(&project_dir, font_metrics)?;
+        let project_path = project_dir
+            .path()
+            .to_str()
+            .context("Expected a UTF-8 path")?
+            .to_owned();
+
+        Ok((project_dir, project_path))
+    }
+
+    /// Replaces the bundled font metrics of a project created with
+    /// [[create_project]], e.g. as if they were regenerated while watching it
+    pub(super) fn write_font_metrics(project_dir: &TempDir, font_metrics: &str) -> Result<()> {
+        fs::write(
+            project_dir
+                .path()
+                .join("node_modules/next/dist/server/capsize-font-metrics.json"),
+            font_metrics,
+        )?;
+        Ok(())
+    }
+
+    fn project_root(project_path: String) -> FileSystemPathVc {
+        DiskFileSystemVc::new("project".to_owned(), project_path)
+            .as_file_system()
+            .root()
+    }
+
+    /// Runs `test` once in a new turbo-tasks instance
+    pub(super) async fn run_once(
+        test: impl Future<Output = Result<()>> + Send + 'static,
+    ) -> Result<()> {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(test).await
+    }
+
+    /// Runs `test` once with the root of a project created with
+    /// [[create_project]] from `font_metrics`
+    pub(super) async fn run_in_project<F, Fut>(font_metrics: &str, test: F) -> Result<()>
+    where
+        F: FnOnce(FileSystemPathVc) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let (_project_dir, project_path) = create_project(font_metrics)?;
+        run_once(async move { test(project_root(project_path)).await }).await
+    }
+
+    /// The options of a request for `font_family` with `arguments`, for a font
+    /// with every weight from 100 to 900
+    pub(super) fn font_options(
+        font_family: &str,
+        arguments: &str,
+    ) -> Result<NextFontGoogleOptionsVc> {
+        Ok(NextFontGoogleOptionsVc::new(Value::new(request_options(
+            font_family,
+            arguments,
+        )?)))
+    }
+
+    /// Like [[font_options]], but not in a cell
+    pub(super) fn request_options(
+        font_family: &str,
+        arguments: &str,
+    ) -> Result<NextFontGoogleOptions> {
+        let data: FontData = parse_json_with_source_context(&format!(
+            r#"
+            {{
+                "{}": {{
+                    "weights": ["100", "200", "300", "400", "500", "600", "700", "800", "900"],
+                    "styles": ["normal"]
+                }}
+            }}
+        "#,
+            font_family
+        ))?;
+        let request: NextFontRequest = parse_json_with_source_context(&format!(
+            r#"
+            {{
+                "import": "{}",
+                "path": "index.js",
+                "variableName": "font",
+                "arguments": [{}]
+            }}
+        "#,
+            font_family.replace(' ', "_"),
+            arguments
+        ))?;
+
+        options_from_request(&request, &data)
+    }
+
+    fn js_asset(project_path: FileSystemPathVc, options: NextFontGoogleOptionsVc) -> AssetVc {
+        NextFontGoogleJsAsset {
+            path: project_path.join("inter.js"),
+            project_path,
+            query: StringVc::cell("".to_owned()),
+            options,
+            request_hash: U32Vc::cell(0xc123b8),
+        }
+        .cell()
+        .as_asset()
+    }
+
+    fn css_asset(project_path: FileSystemPathVc, options: NextFontGoogleOptionsVc) -> AssetVc {
+        NextFontGoogleCssModuleAsset {
+            path: project_path.join("inter.module.css"),
+            project_path,
+            stylesheet: OptionStringVc::cell(None),
+            options,
+            request_hash: U32Vc::cell(0xc123b8),
+        }
+        .cell()
+        .as_asset()
+    }
+
+    async fn read_content(asset: AssetVc) -> Result<String> {
+        let AssetContent::File(content) = *asset.content().await? else {
+            bail!("Expected a file");
+        };
+        let FileContent::Content(file) = &*content.await? else {
+            bail!("Expected the file to exist");
+        };
+
+        Ok(file.content().to_str()?.into_owned())
+    }
+
+    pub(super) async fn issue_titles<T: CollectiblesSource + Copy>(
+        source: T,
+    ) -> Result<Vec<String>> {
+        IssueVc::peek_issues_with_path(source)
+            .await?
+            .strongly_consistent()
+            .await?
+            .iter()
+            .map(|issue| async move { Ok(issue.title().await?.clone_value()) })
+            .try_join()
+            .await
+    }
+
+    /// The titles of the issues with error severity emitted by `source`
+    async fn error_titles<T: CollectiblesSource + Copy>(source: T) -> Result<Vec<String>> {
+        Ok(IssueVc::peek_issues_with_path(source)
+            .await?
+            .strongly_consistent()
+            .await?
+            .iter()
+            .map(|issue| async move {
+                Ok((*issue.severity().await? == IssueSeverity::Error)
+                    .then_some(issue.title().await?.clone_value()))
+            })
+            .try_join()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn test_resolves_fallback_once_module_content_is_read() -> Result<()> {
+        // Without metrics for the font, resolving its fallback emits an issue
+        run_in_project("{}", |project_path| async move {
+            let options = font_options("Inter", r#"{"weight": "400"}"#)?;
+
+            for asset in [
+                js_asset(project_path, options),
+                css_asset(project_path, options),
+            ] {
+                assert_eq!(issue_titles(asset.ident()).await?, Vec::<String>::new());
+                assert_eq!(
+                    issue_titles(asset.content()).await?,
+                    vec!["Failed to find font override values for font `Inter`".to_owned()]
+                );
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Serves the content of `asset` through [[NextFontCheckProcessor]], as the
+    /// dev server does
+    fn serve(context: FileSystemPathVc, asset: AssetVc) -> ContentSourceContentVc {
+        NextFontCheckProcessorVc::new(context)
+            .as_content_source_processor()
+            .process(ContentSourceContentVc::static_content(
+                asset.versioned_content(),
+            ))
+    }
+
+    #[tokio::test]
+    async fn test_warns_when_too_many_fonts_are_served() -> Result<()> {
+        run_in_project(INTER_METRICS, |project_path| async move {
+            let too_many_fonts_warnings = |max_fonts| async move {
+                let options = font_options(
+                    "Inter",
+                    &format!(r#"{{"weight": "400", "max_fonts": {}}}"#, max_fonts),
+                )?;
+                Ok::<_, anyhow::Error>(
+                    issue_titles(serve(project_path, js_asset(project_path, options)))
+                        .await?
+                        .into_iter()
+                        .filter(|title| title.ends_with("fonts are used with next/font"))
+                        .collect::<Vec<_>>(),
+                )
+            };
+
+            assert_eq!(
+                too_many_fonts_warnings(0).await?,
+                vec!["1 fonts are used with next/font".to_owned()]
+            );
+            assert_eq!(too_many_fonts_warnings(1).await?, Vec::<String>::new());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_estimates_cls_improvement_of_served_fonts() -> Result<()> {
+        run_in_project(INTER_METRICS, |project_path| async move {
+            let asset = js_asset(
+                project_path,
+                font_options(
+                    "Inter",
+                    r#"{"weight": "400", "fallback_cls_estimate": true}"#,
+                )?,
+            );
+
+            // Inter's fallback is a `B` match for Arial
+            assert_eq!(
+                issue_titles(serve(project_path, asset)).await?,
+                vec![
+                    "Fallback fonts are estimated to avoid 60% of font-swap layout shift"
+                        .to_owned()
+                ]
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_adds_fallback_timing_to_served_content() -> Result<()> {
+        run_in_project(INTER_METRICS, |project_path| async move {
+            let server_timing = |arguments: &'static str| async move {
+                let asset = js_asset(project_path, font_options("Inter", arguments)?);
+                let ContentSourceContent::Static(static_content) =
+                    *serve(project_path, asset).await?
+                else {
+                    bail!("Expected static content");
+                };
+                Ok::<_, anyhow::Error>(
+                    static_content
+                        .await?
+                        .headers
+                        .await?
+                        .iter()
+                        .find(|(name, _)| name == "Server-Timing")
+                        .map(|(_, value)| value.clone()),
+                )
+            };
+
+            assert_eq!(server_timing(r#"{"weight": "400"}"#).await?, None);
+            let server_timing = server_timing(r#"{"weight": "400", "fallback_timing": true}"#)
+                .await?
+                .context("Expected a Server-Timing header")?;
+            assert!(server_timing.starts_with("font-fallback;desc=\"Inter\";dur="));
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_fails_served_strict_fallbacks() -> Result<()> {
+        // Without metrics, the font's fallback can't be generated
+        run_in_project("{}", |project_path| async move {
+            let asset = js_asset(
+                project_path,
+                font_options("Inter", r#"{"weight": "400", "strict_fallbacks": true}"#)?,
+            );
+
+            assert_eq!(
+                error_titles(serve(project_path, asset)).await?,
+                vec!["Failed to generate fallback fonts for 1 font(s): `Inter`".to_owned()]
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_groups_missing_metrics_issues_of_served_fonts() -> Result<()> {
+        run_in_project("{}", |project_path| async move {
+            let asset = js_asset(
+                project_path,
+                font_options(
+                    "Inter",
+                    r#"{"weight": "400", "group_fallback_issues": true}"#,
+                )?,
+            );
+
+            // The font's own issue is left to the check of the served content
+            assert_eq!(issue_titles(asset.content()).await?, Vec::<String>::new());
+            let served = serve(project_path, asset);
+            assert_eq!(
+                issue_titles(served).await?,
+                vec!["Failed to find font override values for 1 fonts".to_owned()]
+            );
+            let descriptions = IssueVc::peek_issues_with_path(served)
+                .await?
+                .strongly_consistent()
+                .await?
+                .iter()
+                .map(|issue| async move { Ok(issue.description().await?.clone_value()) })
+                .try_join()
+                .await?;
+            assert_eq!(
+                descriptions,
+                vec!["Skipping generating fallback fonts for `Inter`.".to_owned()]
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_warns_when_fallback_exceeds_css_byte_budget() -> Result<()> {
+        run_in_project(INTER_METRICS, |project_path| async move {
+            let budget_warnings = |byte_budget| async move {
+                let options = font_options(
+                    "Inter",
+                    &format!(
+                        r#"{{"weight": "400", "fallback_css_byte_budget": {}}}"#,
+                        byte_budget
+                    ),
+                )?;
+                Ok::<_, anyhow::Error>(
+                    issue_titles(css_asset(project_path, options).content())
+                        .await?
+                        .into_iter()
+                        .filter(|title| title.ends_with("exceeds its CSS byte budget"))
+                        .collect::<Vec<_>>(),
+                )
+            };
+
+            assert_eq!(
+                budget_warnings(0).await?,
+                vec![
+                    "Fallback font `__Inter_Fallback_c123b8` exceeds its CSS byte budget"
+                        .to_owned()
+                ]
+            );
+            assert_eq!(budget_warnings(1000).await?, Vec::<String>::new());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_exports_css_in_js_fallback() -> Result<()> {
+        run_in_project(INTER_METRICS, |project_path| async move {
+            let js = read_content(js_asset(
+                project_path,
+                font_options("Inter", r#"{"weight": "400", "fallback_css_in_js": true}"#)?,
+            ))
+            .await?;
+            assert!(js.contains(
+                r#"fontData.fallback = {"fontFamily":"__Inter_Fallback_c123b8","style":{"ascentOverride":"93.24%","descentOverride":"23.24%","lineGapOverride":"0.00%","sizeAdjust":"103.89%"}};"#
+            ));
+
+            let js = read_content(js_asset(
+                project_path,
+                font_options(
+                    "Inter",
+                    r#"{"weight": "400", "fallback_css_in_js": true, "always_quote_font_families": true}"#,
+                )?,
+            ))
+            .await?;
+            assert!(js.contains(r#"fontData.fallback = {"fontFamily":"'__Inter_Fallback_c123b8'","#));
+
+            let js = read_content(js_asset(
+                project_path,
+                font_options("Inter", r#"{"weight": "400"}"#)?,
+            ))
+            .await?;
+            assert!(!js.contains("fontData.fallback"));
+            Ok(())
+        })
+        .await
+    }
+}
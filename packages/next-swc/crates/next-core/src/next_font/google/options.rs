@@ -1,9 +1,25 @@
+use std::collections::{BTreeMap, HashMap};
+
 use anyhow::{anyhow, Context, Result};
 use indexmap::{indexset, IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
-use turbo_binding::turbo::tasks::{primitives::StringVc, trace::TraceRawVcs, Value};
+use turbo_binding::turbo::tasks::{
+    primitives::{OptionStringVc, StringVc},
+    trace::TraceRawVcs,
+    Value,
+};
 
-use super::request::{NextFontRequest, OneOrManyStrings};
+use super::{
+    request::{NextFontRequest, OneOrManyStrings},
+    util::get_unicode_range,
+};
+use crate::next_font::{
+    font_fallback::find_default_fallback_font,
+    util::{
+        is_css_custom_property_name, CssFormat, DescentSign, FontFamilyQuoting,
+        FontFamilyQuotingVc, OrderedF64,
+    },
+};
 
 const ALLOWED_DISPLAY_VALUES: &[&str] = &["auto", "block", "swap", "fallback", "optional"];
 
@@ -25,6 +41,178 @@ pub(super) struct NextFontGoogleOptions {
     /// the font family when used.
     pub variable: Option<String>,
     pub subsets: Option<Vec<String>>,
+    /// Metrics replacing those bundled with Next.js for specific weights of
+    /// the font, keyed by weight. Applied before looking up the fallback.
+    pub weight_metrics_overrides: Option<BTreeMap<u16, FontMetricsOverride>>,
+    /// The distance from the fallback font's own metrics under which an
+    /// automatic adjustment is considered negligible and omitted.
+    pub adjustment_epsilon: Option<OrderedF64>,
+    /// Whether the automatic fallback should name only the generic font family,
+    /// e.g. `sans-serif`, rather than a specific system font like Arial. It is
+    /// listed in the font family without a fallback `@font-face`.
+    pub fallback_generic_only: bool,
+    /// The font size in pixels the font is expected to be rendered at. When
+    /// set, fallback overrides are rounded to whole pixels at this size to
+    /// avoid sub-pixel differences in line box heights.
+    pub fallback_font_size: Option<u16>,
+    /// Experimental: a factor applied to the automatic fallback's
+    /// `size_adjust`, for teams tuning fallbacks for high-DPI displays.
+    pub experimental_fallback_dpi_scale: Option<OrderedF64>,
+    /// Whether font family names in generated CSS are always quoted, rather
+    /// than only when CSS requires it.
+    pub always_quote_font_families: bool,
+    /// Whether the automatic fallback's `line-gap-override` is always `0%`,
+    /// regardless of the font's metrics, to standardize line heights.
+    pub zero_fallback_line_gap: bool,
+    /// Whether the automatic fallback keeps its full-precision adjustment next
+    /// to the one rounded as rendered in CSS, to see how much precision is
+    /// lost.
+    pub keep_precise_fallback_adjustment: bool,
+    /// Whether fallback overrides are rounded to Safari's layout precision of
+    /// 1/64 pixel, at `fallback_font_size` or 16 pixels, to avoid subtle
+    /// mismatches in Safari.
+    pub safari_fallback_quantization: bool,
+    /// Whether the automatic fallback's `@font-face` is limited to the
+    /// `unicode-range` of the requested subsets, like the webfont itself.
+    pub fallback_unicode_range: bool,
+    /// The automatic fallback's `size_adjust` above which a warning suggests
+    /// choosing a different fallback font, e.g. `1.15`, as the fallback font is
+    /// then a poor match for the font.
+    pub size_adjust_warning_threshold: Option<OrderedF64>,
+    /// Whether the automatic fallback's `@font-face` is declared as its local
+    /// font family, e.g. `Arial`, rather than a scoped font family like
+    /// `__Inter_Fallback_c123b8`, for embedders that don't want scoped names.
+    pub unscoped_fallback_font_family: bool,
+    /// Values replacing individual fields of the automatic fallback's computed
+    /// adjustment, e.g. only its `line_gap`. Omitted fields stay automatic.
+    pub fallback_adjustment_override: Option<FontAdjustmentOverride>,
+    /// Exact metrics of a self-hosted copy of the font, used instead of the
+    /// metrics bundled with Next.js for it.
+    pub self_hosted_metrics: Option<SelfHostedFontMetrics>,
+    /// A `font-feature-settings` value, e.g. `"ss01"`, emitted with the font's
+    /// className rules so stylistic sets apply to the font and its fallback
+    /// alike.
+    pub font_feature_settings: Option<String>,
+    /// The font's Google Fonts family ID, for pipelines tracking fonts by ID.
+    /// See [[NextFontGoogleOptions::lookup_font_family]].
+    pub family_id: Option<String>,
+    /// How much the automatic fallback's `size_adjust` matches the font's
+    /// x-height rather than its average character width, from `0` (width only,
+    /// the default) to `1` (x-height only).
+    pub fallback_x_height_weight: Option<OrderedF64>,
+    /// A font stack listed after the automatic fallback, e.g. Tailwind's
+    /// default `font-sans` stack. The fallback is adjusted against the first
+    /// font of the stack with known metrics, and left out if there is none.
+    /// Ignored if `fallback` is set.
+    pub fallback_preset: Option<FallbackPreset>,
+    /// The most bytes the automatic fallback's `@font-face` rule may take. When
+    /// exceeded, its least impactful overrides are dropped, `line-gap-override`
+    /// first.
+    pub fallback_css_byte_budget: Option<u32>,
+    /// Whether the adjusted fallback `@font-face` is wrapped in a
+    /// `@supports (size-adjust: 100%)` rule, with an unadjusted one declared
+    /// before it for browsers that ignore `size-adjust`.
+    pub fallback_size_adjust_supports_guard: bool,
+    /// Whether a font missing from the bundled metrics is reported without
+    /// suggesting a font with a similar name, skipping the scan of all metrics
+    /// for faster builds.
+    pub disable_font_family_suggestions: bool,
+    /// Whether the automatic fallback is declared once for all requested
+    /// weights, with a `font-weight` range spanning them and their averaged
+    /// adjustment, rather than with the adjustment of the first weight.
+    pub consolidate_fallback_weights: bool,
+    /// How the sign of `descentOverride` is rendered in the fallback exported
+    /// by `fallback_css_in_js`, positive by default. The fallback's
+    /// `@font-face` always declares a positive `descent-override`, as CSS
+    /// requires.
+    pub fallback_descent_sign: DescentSign,
+    /// An optional name for a css custom property (css variable) assigned the
+    /// automatic fallback's scoped font family in the `variable` class, for
+    /// setups referring to fonts by css variables.
+    pub fallback_variable: Option<String>,
+    /// The step in percentage points to round the fallback's `size_adjust` to,
+    /// e.g. `0.5` for the nearest half percent, so small changes to font
+    /// metrics don't change the generated CSS.
+    pub fallback_size_adjust_step: Option<OrderedF64>,
+    /// Whether to mix the version of the font its metrics were measured from
+    /// into the scoped fallback font family, so cached CSS naming the
+    /// fallback is invalidated when the font is updated.
+    pub version_fallback_font_family: bool,
+    /// A ratio of the fallback's descent to its ascent to enforce, e.g. `0.25`,
+    /// for a house style rather than the font's own proportions. The height
+    /// of the line box is kept.
+    pub fallback_descent_ascent_ratio: Option<OrderedF64>,
+    /// The optical size, e.g. the font size in points, the fallback is computed
+    /// for when the font's metrics differ along its optical size (`opsz`) axis
+    pub fallback_optical_size: Option<u16>,
+    /// The named instance of the variable font the fallback stands in for, e.g.
+    /// `Bold`, declared on the fallback's `@font-face` as `font-named-instance`
+    pub fallback_named_instance: Option<String>,
+    /// How to lay out the CSS of the automatic fallback, e.g. minified for
+    /// production, rather than as generated
+    pub fallback_css_format: Option<CssFormat>,
+    /// Whether each font of a manual `fallback` list is checked to be a generic
+    /// font family, a common system font or a Google font, warning about those
+    /// that aren't, e.g. misspelled ones. The list is used as given either way.
+    pub validate_manual_fallback: bool,
+    /// A comment, e.g. a license or attribution notice, declared at the top of
+    /// the font's CSS. Multiple lines are kept as given.
+    pub fallback_css_header: Option<String>,
+    /// Whether the font's JavaScript module also exports its automatic fallback
+    /// as `fallback`, in the shape CSS-in-JS libraries expect for props,
+    /// e.g. to pass to a styled component.
+    pub fallback_css_in_js: bool,
+    /// Whether the raw values of the automatic fallback are reported as an
+    /// informational issue, as a JSON description, for visual font-matching
+    /// tools.
+    pub fallback_preview: bool,
+    /// Whether the override values of the automatic fallback are also emitted
+    /// as CSS custom properties, e.g. `--inter-fallback-ascent: 93.24%;`,
+    /// so they can be inspected in devtools. Meant for development only.
+    pub fallback_debug_custom_properties: bool,
+    /// Whether the `@font-face` of the automatic fallback also contains the
+    /// override values without adjustment in a comment, to compare against by
+    /// uncommenting them. Meant for development only.
+    pub fallback_debug_unadjusted_overrides: bool,
+    /// Whether the `font-family` chain ends with the generic font family of the
+    /// font's category, e.g. `sans-serif`.
+    pub append_generic_font_family: bool,
+    /// The number of distinct fonts of a chunk served by the dev server above
+    /// which [[check_next_fonts]] warns once, suggesting consolidating fonts.
+    /// The lowest number set by any of the fonts applies.
+    pub max_fonts: Option<usize>,
+    /// Whether serving a chunk with the dev server fails when the fallback of
+    /// any of its fonts setting this couldn't be generated, with a single
+    /// error listing them, see [[check_next_fonts]]
+    pub strict_fallbacks: bool,
+    /// Whether the issues of the font's fallback sharing a root cause with
+    /// those of other fonts, e.g. missing metrics, are emitted as a single
+    /// issue listing the fonts of the chunk served by the dev server affected,
+    /// see [[check_next_fonts]], rather than once per font
+    pub group_fallback_issues: bool,
+    /// Whether an estimate of the share of font-swap layout shift the automatic
+    /// fallbacks of a served chunk avoid is reported as an informational
+    /// issue, combining the match quality of each font, see
+    /// [[check_next_fonts]]
+    pub fallback_cls_estimate: bool,
+    /// Whether the time spent resolving the font's fallback is added to the
+    /// `Server-Timing` header of the chunks served by the dev server, for
+    /// debugging performance
+    pub fallback_timing: bool,
+}
+
+/// Google Fonts family IDs with the family name each refers to
+pub(super) type FamilyIdIndex = HashMap<String, String>;
+
+impl NextFontGoogleOptions {
+    /// The family name to look the font's metrics up by: the one its
+    /// `family_id` refers to in `family_ids`, if any, otherwise `font_family`.
+    pub(super) fn lookup_font_family<'a>(&'a self, family_ids: &'a FamilyIdIndex) -> &'a str {
+        self.family_id
+            .as_ref()
+            .and_then(|family_id| family_ids.get(family_id))
+            .unwrap_or(&self.font_family)
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -38,6 +226,27 @@ impl NextFontGoogleOptionsVc {
     pub async fn font_family(self) -> Result<StringVc> {
         Ok(StringVc::cell((*self.await?.font_family).to_owned()))
     }
+
+    #[turbo_tasks::function]
+    pub async fn quoting(self) -> Result<FontFamilyQuotingVc> {
+        Ok(if self.await?.always_quote_font_families {
+            FontFamilyQuoting::Always
+        } else {
+            FontFamilyQuoting::AsNeeded
+        }
+        .cell())
+    }
+
+    /// The `unicode-range` of the requested subsets for the automatic
+    /// fallback's `@font-face`, if `fallback_unicode_range` is set.
+    #[turbo_tasks::function]
+    pub async fn fallback_unicode_range(self) -> Result<OptionStringVc> {
+        let this = self.await?;
+        Ok(OptionStringVc::cell(match &this.subsets {
+            Some(subsets) if this.fallback_unicode_range => get_unicode_range(subsets),
+            _ => None,
+        }))
+    }
 }
 
 #[derive(
@@ -48,6 +257,117 @@ pub(super) enum FontWeights {
     Fixed(Vec<u16>),
 }
 
+/// A predefined font stack to use as a font's fallback, named after the
+/// `fontFamily` defaults of Tailwind CSS
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, TraceRawVcs,
+)]
+#[serde(rename_all = "camelCase")]
+pub(super) enum FallbackPreset {
+    TailwindSans,
+    TailwindSerif,
+    TailwindMono,
+}
+
+impl FallbackPreset {
+    /// The font families of the stack, in order and quoted as needed for a
+    /// `font-family` property.
+    pub(super) fn font_families(self) -> &'static [&'static str] {
+        match self {
+            FallbackPreset::TailwindSans => &[
+                "ui-sans-serif",
+                "system-ui",
+                "-apple-system",
+                "BlinkMacSystemFont",
+                "'Segoe UI'",
+                "Roboto",
+                "'Helvetica Neue'",
+                "Arial",
+                "'Noto Sans'",
+                "sans-serif",
+                "'Apple Color Emoji'",
+                "'Segoe UI Emoji'",
+                "'Segoe UI Symbol'",
+                "'Noto Color Emoji'",
+            ],
+            FallbackPreset::TailwindSerif => &[
+                "ui-serif",
+                "Georgia",
+                "Cambria",
+                "'Times New Roman'",
+                "Times",
+                "serif",
+            ],
+            FallbackPreset::TailwindMono => &[
+                "ui-monospace",
+                "SFMono-Regular",
+                "Menlo",
+                "Monaco",
+                "Consolas",
+                "'Liberation Mono'",
+                "'Courier New'",
+                "monospace",
+            ],
+        }
+    }
+
+    /// The first font of the stack an automatic fallback can be adjusted
+    /// against, e.g. `Arial`, if any. Fonts before it, like `ui-sans-serif`,
+    /// have no known metrics.
+    pub(super) fn adjusted_font_family(self) -> Option<&'static str> {
+        self.font_families()
+            .iter()
+            .map(|font_family| font_family.trim_matches('\''))
+            .find(|font_family| find_default_fallback_font(font_family).is_some())
+    }
+}
+
+/// User-provided values replacing individual fields of an automatic
+/// fallback's computed [[FontAdjustment]], as fractions of the font size like
+/// it. Omitted fields keep their computed value.
+///
+/// [[FontAdjustment]]: crate::next_font::font_fallback::FontAdjustment
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, TraceRawVcs,
+)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct FontAdjustmentOverride {
+    pub ascent: Option<OrderedF64>,
+    pub descent: Option<OrderedF64>,
+    pub line_gap: Option<OrderedF64>,
+    pub size_adjust: Option<OrderedF64>,
+}
+
+/// User-provided metrics of a self-hosted font, in font units like the
+/// bundled metrics they replace.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, TraceRawVcs,
+)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct SelfHostedFontMetrics {
+    /// e.g. `serif`. Keeps the bundled category when omitted.
+    pub category: Option<String>,
+    pub ascent: i32,
+    pub descent: i32,
+    pub line_gap: u32,
+    pub units_per_em: u32,
+    pub x_width_avg: OrderedF64,
+}
+
+/// User-provided values replacing individual fields of a font's bundled
+/// metrics. Omitted fields keep their bundled value.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, TraceRawVcs,
+)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct FontMetricsOverride {
+    pub ascent: Option<i32>,
+    pub descent: Option<i32>,
+    pub line_gap: Option<u32>,
+    pub units_per_em: Option<u32>,
+    pub x_width_avg: Option<OrderedF64>,
+}
+
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize, TraceRawVcs)]
 pub(super) struct FontDataEntry {
     pub weights: Vec<String>,
@@ -180,6 +500,59 @@ pub(super) fn options_from_request(
         }
     }
 
+    if let Some(fallback_variable) = argument.and_then(|a| a.fallback_variable.as_ref()) {
+        if !is_css_custom_property_name(fallback_variable) {
+            return Err(anyhow!(
+                "Invalid fallback variable {} for font {}. Expected a css custom property name, \
+                 e.g. --font-fallback",
+                fallback_variable,
+                font_family
+            ));
+        }
+    }
+
+    if let Some(font_feature_settings) = argument.and_then(|a| a.font_feature_settings.as_ref()) {
+        if font_feature_settings.contains([';', '{', '}']) {
+            return Err(anyhow!(
+                "Invalid font feature settings {} for font {}",
+                font_feature_settings,
+                font_family
+            ));
+        }
+    }
+
+    if argument.and_then(|a| a.fallback_font_size) == Some(0) {
+        return Err(anyhow!(
+            "Invalid fallback font size 0 for font {}. Expected a font size in pixels greater \
+             than 0",
+            font_family
+        ));
+    }
+
+    if let Some(OrderedF64(experimental_fallback_dpi_scale)) =
+        argument.and_then(|a| a.experimental_fallback_dpi_scale)
+    {
+        if !(experimental_fallback_dpi_scale.is_finite() && experimental_fallback_dpi_scale > 0.0) {
+            return Err(anyhow!(
+                "Invalid experimental fallback DPI scale {} for font {}. Expected a finite factor \
+                 greater than 0",
+                experimental_fallback_dpi_scale,
+                font_family
+            ));
+        }
+    }
+
+    if let Some(OrderedF64(adjustment_epsilon)) = argument.and_then(|a| a.adjustment_epsilon) {
+        if !(adjustment_epsilon.is_finite() && adjustment_epsilon >= 0.0) {
+            return Err(anyhow!(
+                "Invalid adjustment epsilon {} for font {}. Expected a finite distance of at \
+                 least 0",
+                adjustment_epsilon,
+                font_family
+            ));
+        }
+    }
+
     Ok(NextFontGoogleOptions {
         font_family,
         weights,
@@ -191,6 +564,74 @@ pub(super) fn options_from_request(
         adjust_font_fallback: argument.map(|a| a.adjust_font_fallback).unwrap_or(true),
         variable: argument.and_then(|a| a.variable.clone()),
         subsets: argument.and_then(|a| a.subsets.clone()),
+        weight_metrics_overrides: argument.and_then(|a| a.weight_metrics_overrides.clone()),
+        adjustment_epsilon: argument.and_then(|a| a.adjustment_epsilon),
+        fallback_generic_only: argument.map(|a| a.fallback_generic_only).unwrap_or(false),
+        fallback_font_size: argument.and_then(|a| a.fallback_font_size),
+        experimental_fallback_dpi_scale: argument.and_then(|a| a.experimental_fallback_dpi_scale),
+        always_quote_font_families: argument
+            .map(|a| a.always_quote_font_families)
+            .unwrap_or(false),
+        zero_fallback_line_gap: argument.map(|a| a.zero_fallback_line_gap).unwrap_or(false),
+        keep_precise_fallback_adjustment: argument
+            .map(|a| a.keep_precise_fallback_adjustment)
+            .unwrap_or(false),
+        safari_fallback_quantization: argument
+            .map(|a| a.safari_fallback_quantization)
+            .unwrap_or(false),
+        fallback_unicode_range: argument.map(|a| a.fallback_unicode_range).unwrap_or(false),
+        size_adjust_warning_threshold: argument.and_then(|a| a.size_adjust_warning_threshold),
+        unscoped_fallback_font_family: argument
+            .map(|a| a.unscoped_fallback_font_family)
+            .unwrap_or(false),
+        fallback_adjustment_override: argument.and_then(|a| a.fallback_adjustment_override.clone()),
+        self_hosted_metrics: argument.and_then(|a| a.self_hosted_metrics.clone()),
+        font_feature_settings: argument.and_then(|a| a.font_feature_settings.clone()),
+        family_id: argument.and_then(|a| a.family_id.clone()),
+        fallback_x_height_weight: argument.and_then(|a| a.fallback_x_height_weight),
+        fallback_preset: argument.and_then(|a| a.fallback_preset),
+        fallback_css_byte_budget: argument.and_then(|a| a.fallback_css_byte_budget),
+        fallback_size_adjust_supports_guard: argument
+            .map(|a| a.fallback_size_adjust_supports_guard)
+            .unwrap_or(false),
+        disable_font_family_suggestions: argument
+            .map(|a| a.disable_font_family_suggestions)
+            .unwrap_or(false),
+        consolidate_fallback_weights: argument
+            .map(|a| a.consolidate_fallback_weights)
+            .unwrap_or(false),
+        fallback_descent_sign: argument
+            .and_then(|a| a.fallback_descent_sign)
+            .unwrap_or_default(),
+        fallback_variable: argument.and_then(|a| a.fallback_variable.clone()),
+        fallback_size_adjust_step: argument.and_then(|a| a.fallback_size_adjust_step),
+        version_fallback_font_family: argument
+            .map(|a| a.version_fallback_font_family)
+            .unwrap_or(false),
+        fallback_descent_ascent_ratio: argument.and_then(|a| a.fallback_descent_ascent_ratio),
+        fallback_optical_size: argument.and_then(|a| a.fallback_optical_size),
+        fallback_named_instance: argument.and_then(|a| a.fallback_named_instance.clone()),
+        fallback_css_format: argument.and_then(|a| a.fallback_css_format),
+        validate_manual_fallback: argument
+            .map(|a| a.validate_manual_fallback)
+            .unwrap_or(false),
+        fallback_css_header: argument.and_then(|a| a.fallback_css_header.clone()),
+        fallback_css_in_js: argument.map(|a| a.fallback_css_in_js).unwrap_or(false),
+        fallback_preview: argument.map(|a| a.fallback_preview).unwrap_or(false),
+        fallback_debug_custom_properties: argument
+            .map(|a| a.fallback_debug_custom_properties)
+            .unwrap_or(false),
+        fallback_debug_unadjusted_overrides: argument
+            .map(|a| a.fallback_debug_unadjusted_overrides)
+            .unwrap_or(false),
+        append_generic_font_family: argument
+            .map(|a| a.append_generic_font_family)
+            .unwrap_or(false),
+        max_fonts: argument.and_then(|a| a.max_fonts),
+        strict_fallbacks: argument.map(|a| a.strict_fallbacks).unwrap_or(false),
+        group_fallback_issues: argument.map(|a| a.group_fallback_issues).unwrap_or(false),
+        fallback_cls_estimate: argument.map(|a| a.fallback_cls_estimate).unwrap_or(false),
+        fallback_timing: argument.map(|a| a.fallback_timing).unwrap_or(false),
     })
 }
 
@@ -200,7 +641,10 @@ mod tests {
     use indexmap::IndexMap;
     use turbo_binding::turbo::tasks_fs::json::parse_json_with_source_context;
 
-    use super::{options_from_request, FontDataEntry, NextFontGoogleOptions};
+    use super::{
+        options_from_request, DescentSign, FallbackPreset, FamilyIdIndex, FontDataEntry,
+        NextFontGoogleOptions,
+    };
     use crate::next_font::google::{options::FontWeights, request::NextFontRequest};
 
     #[test]
@@ -273,6 +717,48 @@ mod tests {
                 adjust_font_fallback: true,
                 variable: None,
                 subsets: None,
+                weight_metrics_overrides: None,
+                adjustment_epsilon: None,
+                fallback_generic_only: false,
+                fallback_font_size: None,
+                experimental_fallback_dpi_scale: None,
+                always_quote_font_families: false,
+                zero_fallback_line_gap: false,
+                keep_precise_fallback_adjustment: false,
+                safari_fallback_quantization: false,
+                fallback_unicode_range: false,
+                size_adjust_warning_threshold: None,
+                unscoped_fallback_font_family: false,
+                fallback_adjustment_override: None,
+                self_hosted_metrics: None,
+                font_feature_settings: None,
+                family_id: None,
+                fallback_x_height_weight: None,
+                fallback_preset: None,
+                fallback_css_byte_budget: None,
+                fallback_size_adjust_supports_guard: false,
+                disable_font_family_suggestions: false,
+                consolidate_fallback_weights: false,
+                fallback_descent_sign: DescentSign::Absolute,
+                fallback_variable: None,
+                fallback_size_adjust_step: None,
+                version_fallback_font_family: false,
+                fallback_descent_ascent_ratio: None,
+                fallback_optical_size: None,
+                fallback_named_instance: None,
+                fallback_css_format: None,
+                validate_manual_fallback: false,
+                fallback_css_header: None,
+                fallback_css_in_js: false,
+                fallback_preview: false,
+                fallback_debug_custom_properties: false,
+                fallback_debug_unadjusted_overrides: false,
+                append_generic_font_family: false,
+                max_fonts: None,
+                strict_fallbacks: false,
+                group_fallback_issues: false,
+                fallback_cls_estimate: false,
+                fallback_timing: false,
             },
         );
 
@@ -537,6 +1023,170 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_errors_on_invalid_fallback_variable() -> Result<()> {
+        let data: IndexMap<String, FontDataEntry> = parse_json_with_source_context(
+            r#"
+            {
+                "ABeeZee": {
+                    "weights": ["400", "variable"],
+                    "styles": ["normal", "italic"]
+                }
+            }
+  "#,
+        )?;
+
+        let request: NextFontRequest = parse_json_with_source_context(
+            r#"
+            {
+                "import": "ABeeZee",
+                "path": "index.js",
+                "variableName": "abeezee",
+                "arguments": [{
+                    "weight": ["400"],
+                    "fallback_variable": "--x: red; } body { color: red"
+                }]
+            }
+        "#,
+        )?;
+
+        match options_from_request(&request, &data) {
+            Ok(_) => panic!(),
+            Err(err) => {
+                assert_eq!(
+                    err.to_string(),
+                    "Invalid fallback variable --x: red; } body { color: red for font ABeeZee. \
+                     Expected a css custom property name, e.g. --font-fallback"
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_on_zero_fallback_font_size() -> Result<()> {
+        let data: IndexMap<String, FontDataEntry> = parse_json_with_source_context(
+            r#"
+            {
+                "ABeeZee": {
+                    "weights": ["400", "variable"],
+                    "styles": ["normal", "italic"]
+                }
+            }
+  "#,
+        )?;
+
+        let request: NextFontRequest = parse_json_with_source_context(
+            r#"
+            {
+                "import": "ABeeZee",
+                "path": "index.js",
+                "variableName": "abeezee",
+                "arguments": [{
+                    "weight": ["400"],
+                    "fallback_font_size": 0
+                }]
+            }
+        "#,
+        )?;
+
+        match options_from_request(&request, &data) {
+            Ok(_) => panic!(),
+            Err(err) => {
+                assert_eq!(
+                    err.to_string(),
+                    "Invalid fallback font size 0 for font ABeeZee. Expected a font size in \
+                     pixels greater than 0"
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_on_invalid_experimental_fallback_dpi_scale() -> Result<()> {
+        let data: IndexMap<String, FontDataEntry> = parse_json_with_source_context(
+            r#"
+            {
+                "ABeeZee": {
+                    "weights": ["400", "variable"],
+                    "styles": ["normal", "italic"]
+                }
+            }
+  "#,
+        )?;
+
+        let request: NextFontRequest = parse_json_with_source_context(
+            r#"
+            {
+                "import": "ABeeZee",
+                "path": "index.js",
+                "variableName": "abeezee",
+                "arguments": [{
+                    "weight": ["400"],
+                    "experimental_fallback_dpi_scale": -1
+                }]
+            }
+        "#,
+        )?;
+
+        match options_from_request(&request, &data) {
+            Ok(_) => panic!(),
+            Err(err) => {
+                assert_eq!(
+                    err.to_string(),
+                    "Invalid experimental fallback DPI scale -1 for font ABeeZee. Expected a \
+                     finite factor greater than 0"
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_on_invalid_adjustment_epsilon() -> Result<()> {
+        let data: IndexMap<String, FontDataEntry> = parse_json_with_source_context(
+            r#"
+            {
+                "ABeeZee": {
+                    "weights": ["400", "variable"],
+                    "styles": ["normal", "italic"]
+                }
+            }
+  "#,
+        )?;
+
+        let request: NextFontRequest = parse_json_with_source_context(
+            r#"
+            {
+                "import": "ABeeZee",
+                "path": "index.js",
+                "variableName": "abeezee",
+                "arguments": [{
+                    "weight": ["400"],
+                    "adjustment_epsilon": -0.5
+                }]
+            }
+        "#,
+        )?;
+
+        match options_from_request(&request, &data) {
+            Ok(_) => panic!(),
+            Err(err) => {
+                assert_eq!(
+                    err.to_string(),
+                    "Invalid adjustment epsilon -0.5 for font ABeeZee. Expected a finite distance \
+                     of at least 0"
+                )
+            }
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_errors_on_axes_without_variable() -> Result<()> {
         let data: IndexMap<String, FontDataEntry> = parse_json_with_source_context(
@@ -576,4 +1226,98 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_resolves_font_family_by_id() -> Result<()> {
+        let data: IndexMap<String, FontDataEntry> = parse_json_with_source_context(
+            r#"
+            {
+                "ABeeZee": {
+                    "weights": ["400"],
+                    "styles": ["normal", "italic"]
+                }
+            }
+  "#,
+        )?;
+        let family_ids = FamilyIdIndex::from([("abz-1".to_owned(), "A Bee Zee".to_owned())]);
+        let options_with_family_id = |family_id: &str| -> Result<NextFontGoogleOptions> {
+            let request: NextFontRequest = parse_json_with_source_context(&format!(
+                r#"
+                {{
+                    "import": "ABeeZee",
+                    "path": "index.js",
+                    "variableName": "abeezee",
+                    "arguments": [{{"weight": "400", "family_id": "{}"}}]
+                }}
+            "#,
+                family_id
+            ))?;
+            options_from_request(&request, &data)
+        };
+
+        assert_eq!(
+            options_with_family_id("abz-1")?.lookup_font_family(&family_ids),
+            "A Bee Zee"
+        );
+        assert_eq!(
+            options_with_family_id("unknown")?.lookup_font_family(&family_ids),
+            "ABeeZee"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback_presets_match_tailwind_font_stacks() -> Result<()> {
+        let data: IndexMap<String, FontDataEntry> = parse_json_with_source_context(
+            r#"
+            {
+                "ABeeZee": {
+                    "weights": ["400"],
+                    "styles": ["normal", "italic"]
+                }
+            }
+  "#,
+        )?;
+        let request: NextFontRequest = parse_json_with_source_context(
+            r#"
+            {
+                "import": "ABeeZee",
+                "path": "index.js",
+                "variableName": "abeezee",
+                "arguments": [{"weight": "400", "fallback_preset": "tailwindSerif"}]
+            }
+        "#,
+        )?;
+        assert_eq!(
+            options_from_request(&request, &data)?.fallback_preset,
+            Some(FallbackPreset::TailwindSerif)
+        );
+
+        assert_eq!(
+            FallbackPreset::TailwindSans.font_families().join(", "),
+            "ui-sans-serif, system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, \
+             'Helvetica Neue', Arial, 'Noto Sans', sans-serif, 'Apple Color Emoji', 'Segoe UI \
+             Emoji', 'Segoe UI Symbol', 'Noto Color Emoji'"
+        );
+        assert_eq!(
+            FallbackPreset::TailwindSerif.font_families().join(", "),
+            "ui-serif, Georgia, Cambria, 'Times New Roman', Times, serif"
+        );
+        assert_eq!(
+            FallbackPreset::TailwindMono.font_families().join(", "),
+            "ui-monospace, SFMono-Regular, Menlo, Monaco, Consolas, 'Liberation Mono', 'Courier \
+             New', monospace"
+        );
+
+        assert_eq!(
+            FallbackPreset::TailwindSans.adjusted_font_family(),
+            Some("Arial")
+        );
+        assert_eq!(
+            FallbackPreset::TailwindSerif.adjusted_font_family(),
+            Some("Times New Roman")
+        );
+        assert_eq!(FallbackPreset::TailwindMono.adjusted_font_family(), None);
+        Ok(())
+    }
 }
@@ -1,5 +1,12 @@
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
 
+use super::options::{
+    FallbackPreset, FontAdjustmentOverride, FontMetricsOverride, SelfHostedFontMetrics,
+};
+use crate::next_font::util::{CssFormat, DescentSign, OrderedF64};
+
 /// The top-most structure encoded into the query param in requests to
 /// `next/font/google` generated by the next/font swc transform. e.g.
 /// `next/font/google/target.css?{"path": "index.js", "import": "Inter"...`
@@ -23,6 +30,69 @@ pub(super) struct NextFontRequestArguments {
     #[serde(default = "default_adjust_font_fallback")]
     pub adjust_font_fallback: bool,
     pub variable: Option<String>,
+    pub weight_metrics_overrides: Option<BTreeMap<u16, FontMetricsOverride>>,
+    pub adjustment_epsilon: Option<OrderedF64>,
+    #[serde(default)]
+    pub fallback_generic_only: bool,
+    pub fallback_font_size: Option<u16>,
+    pub experimental_fallback_dpi_scale: Option<OrderedF64>,
+    #[serde(default)]
+    pub always_quote_font_families: bool,
+    #[serde(default)]
+    pub zero_fallback_line_gap: bool,
+    #[serde(default)]
+    pub keep_precise_fallback_adjustment: bool,
+    #[serde(default)]
+    pub safari_fallback_quantization: bool,
+    #[serde(default)]
+    pub fallback_unicode_range: bool,
+    pub size_adjust_warning_threshold: Option<OrderedF64>,
+    #[serde(default)]
+    pub unscoped_fallback_font_family: bool,
+    pub fallback_adjustment_override: Option<FontAdjustmentOverride>,
+    pub self_hosted_metrics: Option<SelfHostedFontMetrics>,
+    pub font_feature_settings: Option<String>,
+    pub family_id: Option<String>,
+    pub fallback_x_height_weight: Option<OrderedF64>,
+    pub fallback_preset: Option<FallbackPreset>,
+    pub fallback_css_byte_budget: Option<u32>,
+    #[serde(default)]
+    pub fallback_size_adjust_supports_guard: bool,
+    #[serde(default)]
+    pub disable_font_family_suggestions: bool,
+    #[serde(default)]
+    pub consolidate_fallback_weights: bool,
+    pub fallback_descent_sign: Option<DescentSign>,
+    pub fallback_variable: Option<String>,
+    pub fallback_size_adjust_step: Option<OrderedF64>,
+    #[serde(default)]
+    pub version_fallback_font_family: bool,
+    pub fallback_descent_ascent_ratio: Option<OrderedF64>,
+    pub fallback_optical_size: Option<u16>,
+    pub fallback_named_instance: Option<String>,
+    pub fallback_css_format: Option<CssFormat>,
+    #[serde(default)]
+    pub validate_manual_fallback: bool,
+    pub fallback_css_header: Option<String>,
+    #[serde(default)]
+    pub fallback_css_in_js: bool,
+    #[serde(default)]
+    pub fallback_preview: bool,
+    #[serde(default)]
+    pub fallback_debug_custom_properties: bool,
+    #[serde(default)]
+    pub fallback_debug_unadjusted_overrides: bool,
+    #[serde(default)]
+    pub append_generic_font_family: bool,
+    pub max_fonts: Option<usize>,
+    #[serde(default)]
+    pub strict_fallbacks: bool,
+    #[serde(default)]
+    pub group_fallback_issues: bool,
+    #[serde(default)]
+    pub fallback_cls_estimate: bool,
+    #[serde(default)]
+    pub fallback_timing: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -227,6 +227,55 @@ pub(super) fn get_stylesheet_url(
     }
 }
 
+/// The `unicode-range` of each subset, as served by Google Fonts
+static SUBSET_UNICODE_RANGES: &[(&str, &str)] = &[
+    (
+        "cyrillic",
+        "U+0301, U+0400-045F, U+0490-0491, U+04B0-04B1, U+2116",
+    ),
+    (
+        "cyrillic-ext",
+        "U+0460-052F, U+1C80-1C88, U+20B4, U+2DE0-2DFF, U+A640-A69F, U+FE2E-FE2F",
+    ),
+    ("greek", "U+0370-03FF"),
+    ("greek-ext", "U+1F00-1FFF"),
+    (
+        "latin",
+        "U+0000-00FF, U+0131, U+0152-0153, U+02BB-02BC, U+02C6, U+02DA, U+02DC, U+2000-206F, \
+         U+2074, U+20AC, U+2122, U+2191, U+2193, U+2212, U+2215, U+FEFF, U+FFFD",
+    ),
+    (
+        "latin-ext",
+        "U+0100-024F, U+0259, U+1E00-1EFF, U+2020, U+20A0-20AB, U+20AD-20CF, U+2113, U+2C60-2C7F, \
+         U+A720-A7FF",
+    ),
+    (
+        "vietnamese",
+        "U+0102-0103, U+0110-0111, U+0128-0129, U+0168-0169, U+01A0-01A1, U+01AF-01B0, \
+         U+1EA0-1EF9, U+20AB",
+    ),
+];
+
+/// Returns the combined `unicode-range` of `subsets`, e.g. for `["latin"]`.
+/// Returns `None` if any subset's range is unknown, as a fallback limited to a
+/// partial range would leave some of the font's characters without one.
+pub(super) fn get_unicode_range(subsets: &[String]) -> Option<String> {
+    let mut ranges = Vec::with_capacity(subsets.len());
+    for subset in subsets {
+        ranges.push(
+            SUBSET_UNICODE_RANGES
+                .iter()
+                .find(|(name, _)| name == subset)
+                .map(|(_, range)| *range)?,
+        );
+    }
+
+    if ranges.is_empty() {
+        return None;
+    }
+    Some(ranges.join(", "))
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -236,7 +285,7 @@ mod tests {
     use super::get_font_axes;
     use crate::next_font::google::{
         options::{FontData, FontWeights},
-        util::{get_stylesheet_url, FontAxes, FontStyle},
+        util::{get_stylesheet_url, get_unicode_range, FontAxes, FontStyle},
         GOOGLE_FONTS_STYLESHEET_URL,
     };
 
@@ -548,4 +597,24 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_unicode_range_of_subsets() {
+        assert_eq!(
+            get_unicode_range(&["latin".to_owned()]).as_deref(),
+            Some(
+                "U+0000-00FF, U+0131, U+0152-0153, U+02BB-02BC, U+02C6, U+02DA, U+02DC, \
+                 U+2000-206F, U+2074, U+20AC, U+2122, U+2191, U+2193, U+2212, U+2215, U+FEFF, \
+                 U+FFFD"
+            )
+        );
+        assert_eq!(
+            get_unicode_range(&["greek".to_owned(), "greek-ext".to_owned()]).as_deref(),
+            Some("U+0370-03FF, U+1F00-1FFF")
+        );
+        assert_eq!(
+            get_unicode_range(&["latin".to_owned(), "klingon".to_owned()]),
+            None
+        );
+    }
 }
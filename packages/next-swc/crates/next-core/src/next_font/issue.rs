@@ -1,8 +1,34 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    time::Duration,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use turbo_binding::{
     turbo::tasks_fs::FileSystemPathVc,
-    turbopack::core::issue::{Issue, IssueSeverityVc, IssueVc},
+    turbopack::{
+        core::issue::{
+            CapturedIssues, Issue, IssueReporter, IssueReporterVc, IssueSeverity, IssueSeverityVc,
+            IssueVc,
+        },
+        dev_server::source::{
+            wrapping_source::{ContentSourceProcessor, ContentSourceProcessorVc},
+            ContentSourceContent, ContentSourceContentVc, HeaderListVc,
+        },
+    },
+};
+use turbo_tasks::{
+    emit,
+    primitives::{BoolVc, StringVc},
+    trace::TraceRawVcs,
+    CollectiblesSource, RawVc, ReadRef, TransientInstance, TransientValue, TryJoinIterExt,
+};
+
+use super::font_fallback::{
+    check_font_fallbacks, estimate_project_cls_improvement, find_metrics_key_collisions,
+    find_scoped_font_family_collisions, FallbackMatchQuality,
 };
-use turbo_tasks::primitives::StringVc;
 
 #[turbo_tasks::value(shared)]
 pub(crate) struct NextFontIssue {
@@ -12,6 +38,421 @@ pub(crate) struct NextFontIssue {
     pub(crate) severity: IssueSeverityVc,
 }
 
+/// A font resolved through next/font, emitted as a collectible by the task
+/// resolving its fallback, to be aggregated over a module graph by
+/// [[check_next_fonts]]
+#[turbo_tasks::value_trait]
+pub(crate) trait NextFontResolution {
+    fn usage(&self) -> NextFontUsageVc;
+}
+
+impl NextFontResolutionVc {
+    pub(crate) fn emit(self) {
+        emit(self);
+    }
+}
+
+/// How a font was resolved, along with the options of the font concerning all
+/// fonts of a module graph
+#[turbo_tasks::value(shared)]
+pub(crate) struct NextFontUsage {
+    pub(crate) font_family: String,
+    /// The key the font's metrics are looked up by in the bundled metrics, if
+    /// any, see [[find_metrics_key_collisions]]
+    pub(crate) metrics_key: Option<String>,
+    /// The font family the font's automatic fallback is declared as, if any,
+    /// see [[find_scoped_font_family_collisions]]
+    pub(crate) scoped_fallback_font_family: Option<String>,
+    /// The hash of the font's request, telling apart different requests of the
+    /// same font
+    pub(crate) request_hash: u32,
+    /// See `NextFontGoogleOptions::max_fonts`
+    pub(crate) max_fonts: Option<usize>,
+    /// See `NextFontGoogleOptions::strict_fallbacks`
+    pub(crate) strict_fallbacks: bool,
+    /// Whether the font's fallback is [[FontFallback::Error]]
+    pub(crate) fallback_failed: bool,
+    /// The root causes of the font's issues left to [[check_next_fonts]] to
+    /// emit along with those of other fonts, see
+    /// `NextFontGoogleOptions::group_fallback_issues`
+    pub(crate) grouped_causes: Vec<IssueCause>,
+    /// See [[FontAdjustment::match_quality]]. `None` without an adjusted
+    /// automatic fallback.
+    pub(crate) match_quality: Option<FallbackMatchQuality>,
+    /// See `NextFontGoogleOptions::fallback_cls_estimate`
+    pub(crate) cls_estimate: bool,
+    /// The time spent resolving the font's fallback, when asked for by
+    /// `NextFontGoogleOptions::fallback_timing`
+    pub(crate) fallback_duration: Option<Duration>,
+}
+
+#[turbo_tasks::value_impl]
+impl NextFontResolution for NextFontUsage {
+    #[turbo_tasks::function]
+    fn usage(self_vc: NextFontUsageVc) -> NextFontUsageVc {
+        self_vc
+    }
+}
+
+/// Checks the fonts resolved while building the content served by a content
+/// source, e.g. a chunk of a module graph, emitting the issues concerning them
+/// as a whole, see [[check_next_fonts]]. Wraps the dev server's main source.
+#[turbo_tasks::value]
+pub struct NextFontCheckProcessor {
+    context: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl NextFontCheckProcessorVc {
+    #[turbo_tasks::function]
+    pub fn new(context: FileSystemPathVc) -> Self {
+        NextFontCheckProcessor { context }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSourceProcessor for NextFontCheckProcessor {
+    #[turbo_tasks::function]
+    async fn process(&self, content: ContentSourceContentVc) -> Result<ContentSourceContentVc> {
+        let ContentSourceContent::Static(static_content) = *content.await? else {
+            return Ok(content);
+        };
+        let static_content = static_content.await?;
+        let versioned_content = static_content.content;
+
+        // Fonts are resolved either while building the content, e.g. that of a
+        // chunk, or while creating the versioned content, e.g. that of a single
+        // asset
+        let mut resolutions = HashSet::new();
+        for resolutions_of in [
+            versioned_content
+                .content()
+                .peek_collectibles::<NextFontResolutionVc>()
+                .strongly_consistent()
+                .await?,
+            versioned_content
+                .peek_collectibles::<NextFontResolutionVc>()
+                .strongly_consistent()
+                .await?,
+        ] {
+            resolutions.extend(resolutions_of.iter().copied());
+        }
+        let usages = resolutions
+            .into_iter()
+            .map(|resolution| async move { Ok(resolution.usage().await?) })
+            .try_join()
+            .await?;
+
+        for issue in check_next_fonts(&usages.iter().map(|usage| &**usage).collect::<Vec<_>>()) {
+            NextFontIssue {
+                path: self.context,
+                title: StringVc::cell(issue.title),
+                description: StringVc::cell(issue.description),
+                severity: issue.severity.cell(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+
+        let mut fallback_timings = FallbackTimings::default();
+        for usage in usages.iter() {
+            if let Some(duration) = usage.fallback_duration {
+                fallback_timings.record(&usage.font_family, duration);
+            }
+        }
+        if fallback_timings.is_empty() {
+            return Ok(content);
+        }
+        let mut headers = static_content.headers.await?.clone_value();
+        headers.push((
+            "Server-Timing".to_owned(),
+            fallback_timings.to_server_timing(),
+        ));
+        Ok(ContentSourceContentVc::static_with_headers(
+            versioned_content,
+            static_content.status_code,
+            HeaderListVc::cell(headers),
+        ))
+    }
+}
+
+/// The time spent resolving the fallback of each font, keyed by font family
+#[derive(Debug, Default)]
+pub(crate) struct FallbackTimings(BTreeMap<String, Duration>);
+
+impl FallbackTimings {
+    /// Records `duration` for `font_family`, replacing any earlier duration
+    pub(crate) fn record(&mut self, font_family: &str, duration: Duration) {
+        self.0.insert(font_family.to_owned(), duration);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Renders the durations as a `Server-Timing` header value in
+    /// milliseconds, e.g. `font-fallback;desc="Inter";dur=0.125`
+    pub(crate) fn to_server_timing(&self) -> String {
+        self.0
+            .iter()
+            .map(|(font_family, duration)| {
+                format!(
+                    "font-fallback;desc=\"{}\";dur={:.3}",
+                    font_family.replace('\\', "\\\\").replace('"', "\\\""),
+                    duration.as_secs_f64() * 1000.0
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// An issue concerning several fonts at once, returned by [[check_next_fonts]]
+#[derive(Debug, PartialEq)]
+pub(crate) struct NextFontsIssue {
+    pub severity: IssueSeverity,
+    pub title: String,
+    pub description: String,
+}
+
+/// The issues concerning `usages` as a whole, e.g. a single warning
+/// suggesting consolidating fonts when more fonts than allowed by any of them
+/// are used, or a single error listing the fonts whose fallback couldn't be
+/// generated when they ask for strict fallbacks, or one issue per root cause
+/// shared by the issues of several fonts.
+pub(crate) fn check_next_fonts(usages: &[&NextFontUsage]) -> Vec<NextFontsIssue> {
+    let mut issues = vec![];
+
+    let font_count = usages
+        .iter()
+        .map(|usage| usage.font_family.as_str())
+        .collect::<BTreeSet<_>>()
+        .len();
+    if let Some(max_fonts) = usages.iter().filter_map(|usage| usage.max_fonts).min() {
+        if font_count > max_fonts {
+            issues.push(NextFontsIssue {
+                severity: IssueSeverity::Warning,
+                title: format!("{} fonts are used with next/font", font_count),
+                description: format!(
+                    "Each font is downloaded separately, which slows down page loads. Consider \
+                     using at most {} fonts, e.g. by using variable fonts or fewer font families.",
+                    max_fonts
+                ),
+            });
+        }
+    }
+
+    let failed_font_families = usages
+        .iter()
+        .filter(|usage| usage.strict_fallbacks && usage.fallback_failed)
+        .map(|usage| usage.font_family.as_str())
+        .collect::<BTreeSet<_>>();
+    issues.extend(check_font_fallbacks(
+        &failed_font_families.into_iter().collect::<Vec<_>>(),
+    ));
+
+    let collisions = find_scoped_font_family_collisions(usages.iter().filter_map(|usage| {
+        usage
+            .scoped_fallback_font_family
+            .as_deref()
+            .map(|font_family| (font_family, usage.request_hash))
+    }));
+    if !collisions.is_empty() {
+        issues.push(NextFontsIssue {
+            severity: IssueSeverity::Warning,
+            title: "Fallback font families are declared by more than one font request".to_owned(),
+            description: format!(
+                "The fallback `@font-face` rules of {} override each other, so all but one of the \
+                 requests use the wrong adjustment.",
+                collisions
+                    .iter()
+                    .map(|font_family| format!("`{}`", font_family))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        });
+    }
+
+    for (metrics_key, font_families) in
+        find_metrics_key_collisions(usages.iter().filter_map(|usage| {
+            usage
+                .metrics_key
+                .clone()
+                .map(|metrics_key| (metrics_key, usage.font_family.as_str()))
+        }))
+    {
+        issues.push(NextFontsIssue {
+            severity: IssueSeverity::Warning,
+            title: format!(
+                "Font families {} use the same font metrics",
+                font_families
+                    .iter()
+                    .map(|font_family| format!("`{}`", font_family))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ),
+            description: format!(
+                "All are looked up as `{}` in the font metrics. Check that the font family is \
+                 spelled the same way everywhere.",
+                metrics_key
+            ),
+        });
+    }
+
+    if usages.iter().any(|usage| usage.cls_estimate) {
+        issues.extend(estimate_cls_improvement(usages));
+    }
+
+    let mut grouped_issues = GroupedIssueCollector::default();
+    for usage in usages {
+        for cause in &usage.grouped_causes {
+            grouped_issues.record(*cause, &usage.font_family);
+        }
+    }
+    issues.extend(
+        grouped_issues
+            .issues()
+            .into_iter()
+            .map(|issue| NextFontsIssue {
+                severity: issue.cause.severity(),
+                title: issue.title,
+                description: issue.description,
+            }),
+    );
+
+    issues
+}
+
+/// Reports the share of font-swap layout shift avoided by the automatic
+/// fallbacks of `usages`, each font request weighing the same. Fonts without
+/// an adjusted fallback avoid none, and are left out.
+fn estimate_cls_improvement(usages: &[&NextFontUsage]) -> Option<NextFontsIssue> {
+    let fonts = usages
+        .iter()
+        .filter_map(|usage| {
+            usage.match_quality.map(|match_quality| {
+                (
+                    (usage.font_family.as_str(), usage.request_hash),
+                    match_quality,
+                )
+            })
+        })
+        .collect::<BTreeMap<_, _>>();
+    let estimate = estimate_project_cls_improvement(
+        &fonts
+            .values()
+            .map(|match_quality| (*match_quality, 1.0))
+            .collect::<Vec<_>>(),
+    )?;
+
+    Some(NextFontsIssue {
+        severity: IssueSeverity::Info,
+        title: format!(
+            "Fallback fonts are estimated to avoid {:.0}% of font-swap layout shift",
+            estimate * 100.0
+        ),
+        description: format!(
+            "Estimated from the match quality of the fallback of each font: {}.",
+            fonts
+                .iter()
+                .map(|((font_family, _), match_quality)| format!(
+                    "`{}` ({:?})",
+                    font_family, match_quality
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    })
+}
+
+/// A root cause shared by the issues of several fonts
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, TraceRawVcs,
+)]
+pub(crate) enum IssueCause {
+    /// The font has no metrics to generate a fallback font from
+    MissingMetrics,
+}
+
+impl IssueCause {
+    fn severity(&self) -> IssueSeverity {
+        match self {
+            IssueCause::MissingMetrics => IssueSeverity::Warning,
+        }
+    }
+
+    fn title(&self, font_count: usize) -> String {
+        match self {
+            IssueCause::MissingMetrics => {
+                format!(
+                    "Failed to find font override values for {} fonts",
+                    font_count
+                )
+            }
+        }
+    }
+
+    fn description(&self, font_families: &str) -> String {
+        match self {
+            IssueCause::MissingMetrics => {
+                format!("Skipping generating fallback fonts for {}.", font_families)
+            }
+        }
+    }
+}
+
+/// A single issue standing for all fonts affected by `cause`
+#[derive(Debug, PartialEq)]
+pub(crate) struct GroupedIssue {
+    pub cause: IssueCause,
+    pub title: String,
+    pub description: String,
+}
+
+/// Collects the fonts affected by each root cause, to emit one issue per
+/// cause rather than one per font.
+#[derive(Debug, Default)]
+pub(crate) struct GroupedIssueCollector(BTreeMap<IssueCause, BTreeSet<String>>);
+
+impl GroupedIssueCollector {
+    pub(crate) fn record(&mut self, cause: IssueCause, font_family: &str) {
+        self.0
+            .entry(cause)
+            .or_default()
+            .insert(font_family.to_owned());
+    }
+
+    /// One issue per recorded cause, listing its fonts in alphabetical order
+    pub(crate) fn issues(&self) -> Vec<GroupedIssue> {
+        self.0
+            .iter()
+            .map(|(cause, font_families)| GroupedIssue {
+                cause: *cause,
+                title: cause.title(font_families.len()),
+                description: cause.description(
+                    &font_families
+                        .iter()
+                        .map(|font_family| format!("`{}`", font_family))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            })
+            .collect()
+    }
+}
+
+/// The JSON representation of an issue written to stderr by
+/// [[JsonIssueReporter]]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IssueJson<'a> {
+    severity: &'a str,
+    title: &'a str,
+    description: &'a str,
+    path: &'a str,
+}
+
 #[turbo_tasks::value_impl]
 impl Issue for NextFontIssue {
     #[turbo_tasks::function]
@@ -39,3 +480,267 @@ impl Issue for NextFontIssue {
         self.description
     }
 }
+
+/// Reports issues, e.g. those of next/font, to stderr as single-line JSON
+/// objects with their severity, title, description and path, for tooling that
+/// scrapes build output. Meant to be passed to
+/// `NextDevServerBuilder::issue_reporter` in place of the console reporter.
+#[turbo_tasks::value(shared)]
+pub struct JsonIssueReporter;
+
+#[turbo_tasks::value_impl]
+impl JsonIssueReporterVc {
+    #[turbo_tasks::function]
+    pub fn new() -> Self {
+        JsonIssueReporter.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl IssueReporter for JsonIssueReporter {
+    #[turbo_tasks::function]
+    async fn report_issues(
+        &self,
+        captured_issues: TransientInstance<ReadRef<CapturedIssues>>,
+        _source: TransientValue<RawVc>,
+    ) -> Result<BoolVc> {
+        for (issue, path) in captured_issues.iter_with_shortest_path() {
+            let issue = issue.into_plain(path).await?;
+            eprintln!(
+                "{}",
+                serde_json::to_string(&IssueJson {
+                    severity: &format!("{:?}", issue.severity).to_lowercase(),
+                    title: &issue.title,
+                    description: &issue.description,
+                    path: &issue.context,
+                })?
+            );
+        }
+        Ok(BoolVc::cell(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::Result;
+
+    use super::{
+        check_next_fonts, FallbackMatchQuality, FallbackTimings, GroupedIssue,
+        GroupedIssueCollector, IssueCause, IssueJson, NextFontUsage,
+    };
+
+    /// The usage of `font_family` without any option concerning other fonts
+    fn usage(font_family: &str) -> NextFontUsage {
+        NextFontUsage {
+            font_family: font_family.to_owned(),
+            metrics_key: None,
+            scoped_fallback_font_family: None,
+            request_hash: 0,
+            max_fonts: None,
+            strict_fallbacks: false,
+            fallback_failed: false,
+            grouped_causes: vec![],
+            match_quality: None,
+            cls_estimate: false,
+            fallback_duration: None,
+        }
+    }
+
+    /// The titles of the issues of checking `usages`
+    fn check_titles(usages: &[NextFontUsage]) -> Vec<String> {
+        check_next_fonts(&usages.iter().collect::<Vec<_>>())
+            .into_iter()
+            .map(|issue| issue.title)
+            .collect()
+    }
+
+    #[test]
+    fn test_issue_json_is_a_single_line() -> Result<()> {
+        let json = serde_json::to_string(&IssueJson {
+            severity: "warning",
+            title: "Failed to find font override values for font `Foo`",
+            description: "Skipping generating a fallback font.\nCheck the font name.",
+            path: "pages/index.js",
+        })?;
+
+        assert!(!json.contains('\n'));
+        assert_eq!(
+            json,
+            r#"{"severity":"warning","title":"Failed to find font override values for font `Foo`","description":"Skipping generating a fallback font.\nCheck the font name.","path":"pages/index.js"}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_groups_issues_by_cause() {
+        let mut grouped_issues = GroupedIssueCollector::default();
+        for font_family in ["Intr", "Robotto", "Lorra", "Intr"] {
+            grouped_issues.record(IssueCause::MissingMetrics, font_family);
+        }
+
+        assert_eq!(
+            grouped_issues.issues(),
+            vec![GroupedIssue {
+                cause: IssueCause::MissingMetrics,
+                title: "Failed to find font override values for 3 fonts".to_owned(),
+                description: "Skipping generating fallback fonts for `Intr`, `Lorra`, `Robotto`."
+                    .to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_warns_once_when_too_many_fonts_are_used() {
+        let inter = || NextFontUsage {
+            max_fonts: Some(2),
+            ..usage("Inter")
+        };
+        // Inter's JavaScript and CSS modules count as one font
+        let usages = [inter(), inter(), usage("Roboto"), usage("Lora")];
+
+        assert_eq!(
+            check_titles(&usages),
+            vec!["3 fonts are used with next/font".to_owned()]
+        );
+        assert_eq!(check_titles(&usages[..3]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_consolidates_failed_strict_fallbacks() {
+        let failed = |font_family, strict_fallbacks| NextFontUsage {
+            strict_fallbacks,
+            fallback_failed: true,
+            ..usage(font_family)
+        };
+
+        // Lora's fallback failed as well, but it doesn't ask for strict fallbacks
+        assert_eq!(
+            check_titles(&[
+                failed("Roboto", true),
+                failed("Inter", true),
+                failed("Inter", true),
+                failed("Lora", false),
+                usage("Open Sans"),
+            ]),
+            vec!["Failed to generate fallback fonts for 2 font(s): `Inter`, `Roboto`".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_groups_issues_of_fonts() {
+        let missing_metrics = |font_family| NextFontUsage {
+            grouped_causes: vec![IssueCause::MissingMetrics],
+            ..usage(font_family)
+        };
+
+        let issues = check_next_fonts(&[
+            &missing_metrics("Roboto"),
+            &missing_metrics("Inter"),
+            &missing_metrics("Lora"),
+            &usage("Open Sans"),
+        ]);
+        assert_eq!(
+            issues
+                .iter()
+                .map(|issue| (issue.title.as_str(), issue.description.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "Failed to find font override values for 3 fonts",
+                "Skipping generating fallback fonts for `Inter`, `Lora`, `Roboto`."
+            )]
+        );
+    }
+
+    #[test]
+    fn test_warns_about_colliding_scoped_fallback_font_families() {
+        let unscoped = |font_family, request_hash| NextFontUsage {
+            scoped_fallback_font_family: Some("Arial".to_owned()),
+            request_hash,
+            ..usage(font_family)
+        };
+
+        // Inter's JavaScript and CSS modules are the same request
+        assert_eq!(
+            check_titles(&[unscoped("Inter", 1), unscoped("Inter", 1)]),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            check_titles(&[unscoped("Inter", 1), unscoped("Roboto", 2)]),
+            vec!["Fallback font families are declared by more than one font request".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_warns_about_font_families_sharing_metrics() {
+        let looked_up = |font_family: &str| NextFontUsage {
+            metrics_key: Some("robotoSlab".to_owned()),
+            ..usage(font_family)
+        };
+
+        // Roboto Slab's JavaScript and CSS modules
+        assert_eq!(
+            check_titles(&[looked_up("Roboto Slab"), looked_up("Roboto Slab")]),
+            Vec::<String>::new()
+        );
+        let issues = check_next_fonts(&[
+            &looked_up("RobotoSlab"),
+            &looked_up("Roboto Slab"),
+            &usage("Inter"),
+        ]);
+        assert_eq!(
+            issues
+                .iter()
+                .map(|issue| (issue.title.as_str(), issue.description.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "Font families `Roboto Slab` and `RobotoSlab` use the same font metrics",
+                "All are looked up as `robotoSlab` in the font metrics. Check that the font \
+                 family is spelled the same way everywhere."
+            )]
+        );
+    }
+
+    #[test]
+    fn test_estimates_cls_improvement_of_fonts() {
+        let adjusted = |font_family, match_quality| NextFontUsage {
+            match_quality: Some(match_quality),
+            cls_estimate: true,
+            ..usage(font_family)
+        };
+        let usages = [
+            adjusted("Inter", FallbackMatchQuality::A),
+            // Inter's CSS module
+            adjusted("Inter", FallbackMatchQuality::A),
+            adjusted("Roboto", FallbackMatchQuality::C),
+            usage("Lora"),
+        ];
+
+        let issues = check_next_fonts(&usages.iter().collect::<Vec<_>>());
+        assert_eq!(
+            issues
+                .iter()
+                .map(|issue| (issue.title.as_str(), issue.description.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "Fallback fonts are estimated to avoid 60% of font-swap layout shift",
+                "Estimated from the match quality of the fallback of each font: `Inter` (A), \
+                 `Roboto` (C)."
+            )]
+        );
+        assert_eq!(check_titles(&[usage("Inter")]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_renders_fallback_timings_as_server_timing() {
+        let mut fallback_timings = FallbackTimings::default();
+        fallback_timings.record("Roboto Slab", Duration::from_micros(1250));
+        fallback_timings.record("Inter", Duration::from_micros(125));
+
+        assert_eq!(
+            fallback_timings.to_server_timing(),
+            "font-fallback;desc=\"Inter\";dur=0.125, font-fallback;desc=\"Roboto Slab\";dur=1.250"
+        );
+    }
+}
@@ -1,22 +1,28 @@
 use allsorts::{
+    binary::read::ReadScope,
     font_data::{DynamicFontTableProvider, FontData},
-    Font,
+    tables::{FontTableProvider, NameTable},
+    tag, Font,
 };
 use anyhow::{bail, Context, Result};
-use turbo_binding::turbo::{
-    tasks::primitives::{StringVc, StringsVc, U32Vc},
-    tasks_fs::{FileContent, FileSystemPathVc},
+use turbo_binding::{
+    turbo::{
+        tasks::primitives::{StringVc, StringsVc, U32Vc},
+        tasks_fs::{FileContent, FileSystemPathVc},
+    },
+    turbopack::core::issue::IssueSeverity,
 };
 
 use super::{
     options::{FontDescriptor, FontDescriptors, FontWeight, NextFontLocalOptionsVc},
-    request::AdjustFontFallback,
+    request::{AdjustFontFallback, FontFaceSelector},
 };
 use crate::next_font::{
     font_fallback::{
         AutomaticFontFallback, DefaultFallbackFont, FontAdjustment, FontFallback, FontFallbacksVc,
         DEFAULT_SANS_SERIF_FONT, DEFAULT_SERIF_FONT,
     },
+    issue::{NextFontIssue, NextFontUsage},
     util::{get_scoped_font_family, FontFamilyType},
 };
 
@@ -25,6 +31,14 @@ use crate::next_font::{
 static AVG_CHARACTERS: &str = "aaabcdeeeefghiijklmnnoopqrrssttuvwxyz      ";
 static NORMAL_WEIGHT: f64 = 400.0;
 static BOLD_WEIGHT: f64 = 700.0;
+/// Tables holding color glyphs. Fonts with any of these are color or emoji
+/// fonts, which a text fallback font can't approximate.
+static COLOR_FONT_TABLES: [u32; 4] = [
+    u32::from_be_bytes(*b"COLR"),
+    u32::from_be_bytes(*b"sbix"),
+    u32::from_be_bytes(*b"CBDT"),
+    u32::from_be_bytes(*b"SVG "),
+];
 
 #[turbo_tasks::function]
 pub(super) async fn get_font_fallbacks(
@@ -40,35 +54,78 @@ pub(super) async fn get_font_fallbacks(
         request_hash,
     );
 
-    match options.adjust_font_fallback {
-        AdjustFontFallback::Arial => font_fallbacks.push(
-            FontFallback::Automatic(
-                AutomaticFontFallback {
-                    scoped_font_family,
-                    local_font_family: StringVc::cell("Arial".to_owned()),
-                    adjustment: Some(
-                        get_font_adjustment(context, options_vc, &DEFAULT_SANS_SERIF_FONT).await?,
-                    ),
-                }
-                .cell(),
-            )
-            .into(),
-        ),
-        AdjustFontFallback::TimesNewRoman => font_fallbacks.push(
-            FontFallback::Automatic(
-                AutomaticFontFallback {
-                    scoped_font_family,
-                    local_font_family: StringVc::cell("Times New Roman".to_owned()),
-                    adjustment: Some(
-                        get_font_adjustment(context, options_vc, &DEFAULT_SERIF_FONT).await?,
+    let fallback_font = match options.adjust_font_fallback {
+        AdjustFontFallback::Arial => Some(&*DEFAULT_SANS_SERIF_FONT),
+        AdjustFontFallback::TimesNewRoman => Some(&*DEFAULT_SERIF_FONT),
+        AdjustFontFallback::None => None,
+    };
+    NextFontUsage {
+        font_family: options_vc.font_family().await?.clone_value(),
+        // Local fonts are measured from their files rather than looked up
+        metrics_key: None,
+        scoped_fallback_font_family: match fallback_font {
+            Some(_) => Some(scoped_font_family.await?.clone_value()),
+            None => None,
+        },
+        request_hash: *request_hash.await?,
+        max_fonts: options.max_fonts,
+        // Local fonts fall back to generating no adjustment rather than failing
+        strict_fallbacks: false,
+        fallback_failed: false,
+        grouped_causes: vec![],
+        // Local fonts aren't measured against the default fallback fonts' metrics
+        match_quality: None,
+        cls_estimate: false,
+        fallback_duration: None,
+    }
+    .cell()
+    .as_next_font_resolution()
+    .emit();
+    if let Some(fallback_font) = fallback_font {
+        let main_descriptor = pick_font_for_fallback_generation(&options.fonts)?;
+        let font_file = read_font_file(context, &main_descriptor.path).await?;
+        match get_font_adjustment(&font_file, main_descriptor, fallback_font)? {
+            Some(adjustment) => font_fallbacks.push(
+                FontFallback::Automatic(
+                    AutomaticFontFallback {
+                        scoped_font_family,
+                        local_font_family: StringVc::cell(fallback_font.name.clone()),
+                        adjustment: Some(adjustment),
+                        precise_adjustment: None,
+                        css_byte_budget: None,
+                        size_adjust_supports_guard: false,
+                        font_weight_range: None,
+                        variable: None,
+                        named_instance: None,
+                        css_format: None,
+                        debug_custom_properties: false,
+                        debug_unadjusted_overrides: false,
+                        font_stack: vec![],
+                    }
+                    .cell(),
+                )
+                .into(),
+            ),
+            None => {
+                NextFontIssue {
+                    path: context,
+                    title: StringVc::cell(format!(
+                        "Skipping fallback font generation for color font `{}`",
+                        *options_vc.font_family().await?,
+                    )),
+                    description: StringVc::cell(
+                        "Emoji and color fonts can't be approximated by a text fallback font."
+                            .to_owned(),
                     ),
+                    severity: IssueSeverity::Info.cell(),
                 }
-                .cell(),
-            )
-            .into(),
-        ),
-        AdjustFontFallback::None => (),
-    };
+                .cell()
+                .as_issue()
+                .emit();
+                font_fallbacks.push(FontFallback::Skipped.into());
+            }
+        }
+    }
 
     if let Some(fallback) = &options.fallback {
         font_fallbacks.push(FontFallback::Manual(StringsVc::cell(fallback.clone())).into());
@@ -77,24 +134,40 @@ pub(super) async fn get_font_fallbacks(
     Ok(FontFallbacksVc::cell(font_fallbacks))
 }
 
-async fn get_font_adjustment(
-    context: FileSystemPathVc,
-    options: NextFontLocalOptionsVc,
-    fallback_font: &DefaultFallbackFont,
-) -> Result<FontAdjustment> {
-    let options = &*options.await?;
-    let main_descriptor = pick_font_for_fallback_generation(&options.fonts)?;
-    let font_file = &*context.join(&main_descriptor.path).read().await?;
+/// Whether any of [[COLOR_FONT_TABLES]] is present according to `has_table`.
+fn has_color_table(has_table: impl Fn(u32) -> bool) -> bool {
+    COLOR_FONT_TABLES.iter().any(|tag| has_table(*tag))
+}
+
+async fn read_font_file(context: FileSystemPathVc, path: &str) -> Result<Vec<u8>> {
+    let font_file = &*context.join(path).read().await?;
     let font_file_rope = match font_file {
         FileContent::NotFound => bail!("Expected font file content"),
         FileContent::Content(file) => file.content(),
     };
 
-    let font_file_binary = font_file_rope.to_bytes()?;
-    let scope = allsorts::binary::read::ReadScope::new(&font_file_binary);
-    let mut font = Font::new(scope.read::<FontData>()?.table_provider(0)?)?.context(format!(
+    Ok(font_file_rope.to_bytes()?.into_owned())
+}
+
+/// Computes the adjustment of `fallback_font` for the face of `font_file`
+/// chosen by `descriptor`. `None` for color fonts, see [[COLOR_FONT_TABLES]],
+/// which a text fallback font can't approximate.
+fn get_font_adjustment(
+    font_file: &[u8],
+    descriptor: &FontDescriptor,
+    fallback_font: &DefaultFallbackFont,
+) -> Result<Option<FontAdjustment>> {
+    let scope = ReadScope::new(font_file);
+    let font_data = scope.read::<FontData>()?;
+    let table_provider =
+        font_data.table_provider(get_face_index(font_file, &font_data, descriptor)?)?;
+    if has_color_table(|tag| table_provider.has_table(tag)) {
+        return Ok(None);
+    }
+
+    let mut font = Font::new(table_provider)?.context(format!(
         "Unable to read font metrics from font file at {}",
-        &main_descriptor.path,
+        &descriptor.path,
     ))?;
 
     let az_avg_width = calc_average_width(&mut font);
@@ -102,22 +175,156 @@ async fn get_font_adjustment(
         .head_table()?
         .context(format!(
             "Unable to read font scale from font file at {}",
-            &main_descriptor.path
+            &descriptor.path
         ))?
         .units_per_em as f64;
 
-    let fallback_avg_width = fallback_font.x_width_avg / fallback_font.units_per_em as f64;
-    let size_adjust = match az_avg_width {
-        Some(az_avg_width) => az_avg_width as f64 / units_per_em / fallback_avg_width,
+    let os2_table = font.font_table_provider.table_data(tag::OS_2)?;
+
+    // Prefer the average measured from glyph advances, as capsize does for the
+    // Google fonts metrics, then the average stored in the font's OS/2 table.
+    let x_width_avg = match az_avg_width {
+        Some(az_avg_width) => Some(az_avg_width as f64),
+        None => os2_table.as_deref().and_then(read_os2_x_avg_char_width),
+    };
+    let size_adjust = match x_width_avg {
+        Some(x_width_avg) => fallback_font.size_adjust_for(x_width_avg, units_per_em),
         None => 1.0,
     };
 
-    Ok(FontAdjustment {
-        ascent: font.hhea_table.ascender as f64 / (units_per_em * size_adjust),
-        descent: font.hhea_table.descender as f64 / (units_per_em * size_adjust),
-        line_gap: font.hhea_table.line_gap as f64 / (units_per_em * size_adjust),
+    // Fonts setting USE_TYPO_METRICS ask for their typographic metrics to be
+    // used for line layout instead of the hhea ones, as browsers then do
+    let (ascent, descent, line_gap) = os2_table
+        .as_deref()
+        .and_then(read_os2_typo_metrics)
+        .unwrap_or((
+            font.hhea_table.ascender,
+            font.hhea_table.descender,
+            font.hhea_table.line_gap,
+        ));
+
+    Ok(Some(FontAdjustment {
+        ascent: ascent as f64 / (units_per_em * size_adjust),
+        descent: descent as f64 / (units_per_em * size_adjust),
+        line_gap: line_gap as f64 / (units_per_em * size_adjust),
         size_adjust,
-    })
+    }))
+}
+
+/// Returns the index of the face in `font_file` chosen by `descriptor`.
+fn get_face_index(
+    font_file: &[u8],
+    font_data: &FontData,
+    descriptor: &FontDescriptor,
+) -> Result<usize> {
+    let face_count = read_collection_face_count(font_file).unwrap_or(1);
+    let face_names = (0..face_count)
+        .map(|index| read_postscript_name(font_data, index))
+        .collect::<Result<Vec<_>>>()?;
+
+    select_face(&face_names, descriptor.face.as_ref()).context(format!(
+        "Unable to select a face from font file at {}",
+        &descriptor.path,
+    ))
+}
+
+/// Returns the number of faces in `font_file` if it is a font collection
+/// (`.ttc`), as given by its header, or `None` if it is a single font.
+fn read_collection_face_count(font_file: &[u8]) -> Option<usize> {
+    // `ttcf` tag, major and minor version, then the number of fonts and an
+    // offset to each of them
+    if font_file.get(0..4)? != b"ttcf" {
+        return None;
+    }
+
+    let face_count = u32::from_be_bytes(font_file.get(8..12)?.try_into().ok()?) as usize;
+    let offsets_end = 12 + face_count.checked_mul(4)?;
+    if font_file.len() < offsets_end {
+        return None;
+    }
+
+    Some(face_count)
+}
+
+/// Reads the PostScript name of the face at `index`, if it has one.
+fn read_postscript_name(font_data: &FontData, index: usize) -> Result<Option<String>> {
+    let table_provider = font_data.table_provider(index)?;
+    let Some(name_data) = table_provider.table_data(tag::NAME)? else {
+        return Ok(None)
+    };
+
+    let name_table = ReadScope::new(&name_data).read::<NameTable>()?;
+    Ok(name_table.string_for_id(NameTable::POSTSCRIPT_NAME))
+}
+
+/// Returns the index of the face chosen by `selector`, given the PostScript
+/// names of every face in a font file. Uses the first face if no face is
+/// chosen.
+fn select_face(
+    face_names: &[Option<String>],
+    selector: Option<&FontFaceSelector>,
+) -> Result<usize> {
+    match selector {
+        None => Ok(0),
+        Some(FontFaceSelector::Index(index)) => {
+            if *index < face_names.len() {
+                Ok(*index)
+            } else {
+                bail!(
+                    "Face index {} is out of range, the font file has {} face(s)",
+                    index,
+                    face_names.len()
+                )
+            }
+        }
+        Some(FontFaceSelector::PostScriptName(name)) => face_names
+            .iter()
+            .position(|face_name| face_name.as_deref() == Some(name.as_str()))
+            .with_context(|| format!("No face named `{}` in the font file", name)),
+    }
+}
+
+/// Reads `xAvgCharWidth` from the raw data of an OS/2 table. The field is
+/// present from version 0 on, but tables shorter than their version requires
+/// are malformed and ignored. A value of zero means the width is unknown.
+fn read_os2_x_avg_char_width(os2_table: &[u8]) -> Option<f64> {
+    let version = u16::from_be_bytes(os2_table.get(0..2)?.try_into().ok()?);
+    let min_length = match version {
+        // Some old Apple fonts end version 0 tables after `usLastCharIndex`
+        0 => 68,
+        1 => 86,
+        2..=4 => 96,
+        _ => 100,
+    };
+    if os2_table.len() < min_length {
+        return None;
+    }
+
+    let x_avg_char_width = i16::from_be_bytes(os2_table.get(2..4)?.try_into().ok()?);
+    (x_avg_char_width > 0).then_some(x_avg_char_width as f64)
+}
+
+/// Bit of `fsSelection` asking for the typographic metrics to be used
+const USE_TYPO_METRICS: u16 = 1 << 7;
+
+/// Reads `sTypoAscender`, `sTypoDescender` and `sTypoLineGap` from the raw
+/// data of an OS/2 table, if the table sets USE_TYPO_METRICS. The bit is
+/// defined from version 4 on.
+fn read_os2_typo_metrics(os2_table: &[u8]) -> Option<(i16, i16, i16)> {
+    let read_u16 = |offset: usize| {
+        Some(u16::from_be_bytes(
+            os2_table.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+    if read_u16(0)? < 4 || os2_table.len() < 96 || read_u16(62)? & USE_TYPO_METRICS == 0 {
+        return None;
+    }
+
+    Some((
+        read_u16(68)? as i16,
+        read_u16(70)? as i16,
+        read_u16(72)? as i16,
+    ))
 }
 
 fn calc_average_width(font: &mut Font<DynamicFontTableProvider>) -> Option<f32> {
@@ -261,19 +468,33 @@ fn parse_weight_string(weight_str: &str) -> Result<f64> {
 // From https://github.com/vercel/next.js/blob/7457be0c74e64b4d0617943ed27f4d557cc916be/packages/font/src/local/pick-font-file-for-fallback-generation.test.ts
 #[cfg(test)]
 mod tests {
-    use anyhow::Result;
-
-    use crate::next_font::local::{
-        font_fallback::pick_font_for_fallback_generation,
-        options::{FontDescriptor, FontDescriptors, FontWeight},
+    use anyhow::{Context, Result};
+
+    use crate::next_font::{
+        font_fallback::{FontAdjustment, DEFAULT_SANS_SERIF_FONT},
+        local::{
+            font_fallback::{
+                get_font_adjustment, has_color_table, pick_font_for_fallback_generation,
+                read_collection_face_count, read_os2_x_avg_char_width, select_face,
+                COLOR_FONT_TABLES,
+            },
+            options::{FontDescriptor, FontDescriptors, FontWeight},
+            request::FontFaceSelector,
+        },
     };
 
+    /// A font file with a single face and no color glyphs
+    const NOTO_SANS: &[u8] = include_bytes!(
+        "../../../../../../next/src/compiled/@vercel/og/noto-sans-v27-latin-regular.ttf"
+    );
+
     fn generate_font_descriptor(weight: &FontWeight, style: &Option<String>) -> FontDescriptor {
         FontDescriptor {
             ext: "ttf".to_owned(),
             path: "foo.ttf".to_owned(),
             style: style.clone(),
             weight: Some(weight.clone()),
+            face: None,
         }
     }
 
@@ -405,6 +626,37 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_picks_regular_face_of_font_family_group() -> Result<()> {
+        // A single fallback is generated for all faces of a `localFont` call,
+        // adjusted for its regular face
+        let regular = generate_font_descriptor(
+            &FontWeight::Fixed("400".to_owned()),
+            &Some("normal".to_owned()),
+        );
+        let italic = generate_font_descriptor(
+            &FontWeight::Fixed("400".to_owned()),
+            &Some("italic".to_owned()),
+        );
+        let bold = generate_font_descriptor(
+            &FontWeight::Fixed("700".to_owned()),
+            &Some("normal".to_owned()),
+        );
+
+        for faces in [
+            vec![regular.clone(), italic.clone(), bold.clone()],
+            vec![italic.clone(), bold.clone(), regular.clone()],
+            vec![bold.clone(), regular.clone(), italic.clone()],
+        ] {
+            assert_eq!(
+                pick_font_for_fallback_generation(&FontDescriptors::Many(faces))?,
+                &regular
+            );
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_errors_on_invalid_weight() -> Result<()> {
         match pick_font_for_fallback_generation(&FontDescriptors::Many(vec![
@@ -437,4 +689,206 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_reads_font_collection_header() {
+        // Header of a collection with two faces at offsets 20 and 2068
+        let collection: &[u8] = &[
+            b't', b't', b'c', b'f', 0, 1, 0, 0, 0, 0, 0, 2, 0, 0, 0, 20, 0, 0, 8, 20,
+        ];
+        assert_eq!(read_collection_face_count(collection), Some(2));
+        assert_eq!(read_collection_face_count(&collection[..16]), None);
+        assert_eq!(read_collection_face_count(&[0, 1, 0, 0, 0, 12]), None);
+    }
+
+    #[test]
+    fn test_selects_face_from_collection() -> Result<()> {
+        let face_names = vec![
+            Some("Example-Regular".to_owned()),
+            Some("Example-Bold".to_owned()),
+        ];
+
+        assert_eq!(select_face(&face_names, None)?, 0);
+        assert_eq!(
+            select_face(&face_names, Some(&FontFaceSelector::Index(1)))?,
+            1
+        );
+        assert_eq!(
+            select_face(
+                &face_names,
+                Some(&FontFaceSelector::PostScriptName("Example-Bold".to_owned()))
+            )?,
+            1
+        );
+        assert!(select_face(&face_names, Some(&FontFaceSelector::Index(2))).is_err());
+        assert!(select_face(
+            &face_names,
+            Some(&FontFaceSelector::PostScriptName(
+                "Example-Italic".to_owned()
+            ))
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detects_color_font_tables() {
+        let colr = u32::from_be_bytes(*b"COLR");
+        let glyf = u32::from_be_bytes(*b"glyf");
+
+        assert!(has_color_table(|tag| tag == colr || tag == glyf));
+        assert!(has_color_table(|tag| COLOR_FONT_TABLES[1] == tag));
+        assert!(!has_color_table(|tag| tag == glyf));
+    }
+
+    #[test]
+    fn test_skips_adjustment_of_color_fonts() -> Result<()> {
+        let descriptor = generate_font_descriptor(&FontWeight::Fixed("400".to_owned()), &None);
+        assert!(get_font_adjustment(NOTO_SANS, &descriptor, &DEFAULT_SANS_SERIF_FONT)?.is_some());
+
+        // Rename the first table, `GDEF`, keeping the table records sorted
+        let mut color_font = NOTO_SANS.to_vec();
+        color_font[12..16].copy_from_slice(b"COLR");
+        assert_eq!(
+            get_font_adjustment(&color_font, &descriptor, &DEFAULT_SANS_SERIF_FONT)?,
+            None
+        );
+        Ok(())
+    }
+
+    /// Returns the offset of the table tagged `tag` in a single face font file
+    fn table_offset(font_file: &[u8], tag: &[u8; 4]) -> usize {
+        let num_tables = u16::from_be_bytes([font_file[4], font_file[5]]) as usize;
+        let record = (0..num_tables)
+            .map(|index| 12 + 16 * index)
+            .find(|record| &font_file[*record..*record + 4] == tag)
+            .unwrap();
+        u32::from_be_bytes(font_file[record + 8..record + 12].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn test_prefers_typo_metrics_if_font_asks_to() -> Result<()> {
+        let descriptor = generate_font_descriptor(&FontWeight::Fixed("400".to_owned()), &None);
+        let adjustment = |font_file: &[u8]| -> Result<FontAdjustment> {
+            get_font_adjustment(font_file, &descriptor, &DEFAULT_SANS_SERIF_FONT)?
+                .context("Expected an adjustment")
+        };
+        // Noto Sans sets USE_TYPO_METRICS, with typo metrics equal to its hhea ones
+        let noto_sans = adjustment(NOTO_SANS)?;
+
+        let mut font_file = NOTO_SANS.to_vec();
+        let hhea = table_offset(&font_file, b"hhea");
+        font_file[hhea + 4..hhea + 6].copy_from_slice(&1200i16.to_be_bytes());
+        assert_eq!(adjustment(&font_file)?, noto_sans);
+
+        let os2 = table_offset(&font_file, b"OS/2");
+        font_file[os2 + 63] &= !0x80;
+        let hhea_adjustment = adjustment(&font_file)?;
+        assert_eq!(
+            hhea_adjustment.ascent,
+            1200.0 / (1000.0 * noto_sans.size_adjust)
+        );
+        assert_eq!(hhea_adjustment.descent, noto_sans.descent);
+        Ok(())
+    }
+
+    /// Builds a font collection of single face `faces`, moving the offsets of
+    /// their tables to the position of each face in the collection
+    fn generate_font_collection(faces: &[Vec<u8>]) -> Vec<u8> {
+        let mut collection = b"ttcf".to_vec();
+        collection.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        collection.extend_from_slice(&(faces.len() as u32).to_be_bytes());
+        let mut face_offset = 12 + 4 * faces.len();
+        for face in faces {
+            collection.extend_from_slice(&(face_offset as u32).to_be_bytes());
+            face_offset += (face.len() + 3) & !3;
+        }
+
+        for face in faces {
+            let base = collection.len();
+            collection.extend_from_slice(face);
+            collection.resize((collection.len() + 3) & !3, 0);
+            let num_tables = u16::from_be_bytes([face[4], face[5]]) as usize;
+            for record in (0..num_tables).map(|index| base + 12 + 16 * index) {
+                let offset = &mut collection[record + 8..record + 12];
+                let moved = u32::from_be_bytes((&*offset).try_into().unwrap()) + base as u32;
+                offset.copy_from_slice(&moved.to_be_bytes());
+            }
+        }
+        collection
+    }
+
+    #[test]
+    fn test_adjusts_chosen_face_of_font_collection() -> Result<()> {
+        // A second face with a taller typo ascender
+        let mut tall_face = NOTO_SANS.to_vec();
+        let os2 = table_offset(&tall_face, b"OS/2");
+        tall_face[os2 + 68..os2 + 70].copy_from_slice(&1200i16.to_be_bytes());
+        let collection = generate_font_collection(&[NOTO_SANS.to_vec(), tall_face]);
+        assert_eq!(read_collection_face_count(&collection), Some(2));
+
+        let adjustment = |face: Option<FontFaceSelector>| -> Result<FontAdjustment> {
+            let descriptor = FontDescriptor {
+                face,
+                ..generate_font_descriptor(&FontWeight::Fixed("400".to_owned()), &None)
+            };
+            get_font_adjustment(&collection, &descriptor, &DEFAULT_SANS_SERIF_FONT)?
+                .context("Expected an adjustment")
+        };
+        let noto_sans = get_font_adjustment(
+            NOTO_SANS,
+            &generate_font_descriptor(&FontWeight::Fixed("400".to_owned()), &None),
+            &DEFAULT_SANS_SERIF_FONT,
+        )?
+        .context("Expected an adjustment")?;
+
+        assert_eq!(adjustment(None)?, noto_sans);
+        assert_eq!(adjustment(Some(FontFaceSelector::Index(0)))?, noto_sans);
+        let tall = adjustment(Some(FontFaceSelector::Index(1)))?;
+        assert_eq!(tall.ascent, 1200.0 / (1000.0 * noto_sans.size_adjust));
+        assert_eq!(tall.descent, noto_sans.descent);
+        assert!(adjustment(Some(FontFaceSelector::Index(2))).is_err());
+        Ok(())
+    }
+
+    fn generate_os2_table(version: u16, length: usize, x_avg_char_width: i16) -> Vec<u8> {
+        let mut os2_table = vec![0; length];
+        os2_table[0..2].copy_from_slice(&version.to_be_bytes());
+        os2_table[2..4].copy_from_slice(&x_avg_char_width.to_be_bytes());
+        os2_table
+    }
+
+    #[test]
+    fn test_reads_os2_x_avg_char_width() {
+        assert_eq!(
+            read_os2_x_avg_char_width(&generate_os2_table(4, 96, 1000)),
+            Some(1000.0)
+        );
+        assert_eq!(
+            read_os2_x_avg_char_width(&generate_os2_table(0, 68, 1000)),
+            Some(1000.0)
+        );
+        assert_eq!(
+            read_os2_x_avg_char_width(&generate_os2_table(5, 100, 1000)),
+            Some(1000.0)
+        );
+        // Truncated for its version
+        assert_eq!(
+            read_os2_x_avg_char_width(&generate_os2_table(2, 86, 1000)),
+            None
+        );
+        // Unknown width
+        assert_eq!(
+            read_os2_x_avg_char_width(&generate_os2_table(4, 96, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_os2_x_avg_char_width_drives_size_adjust() {
+        let x_avg_char_width = read_os2_x_avg_char_width(&generate_os2_table(4, 96, 1000)).unwrap();
+        let size_adjust = DEFAULT_SANS_SERIF_FONT.size_adjust_for(x_avg_char_width, 2048.0);
+        assert!((size_adjust - 1000.0 / 934.5116279069767).abs() < 1e-12);
+    }
 }
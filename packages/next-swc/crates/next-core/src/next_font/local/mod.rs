@@ -178,6 +178,7 @@ impl ImportMappingReplacement for NextFontLocalCssModuleReplacer {
         let fallback = get_font_fallbacks(context, options, request_hash);
 
         let stylesheet = build_stylesheet(
+            context,
             font_options_from_query_map(*query_vc),
             fallback,
             get_font_css_properties(options, fallback, request_hash),
@@ -222,6 +223,7 @@ async fn get_font_css_properties(
             // className selector rules
             FontDescriptors::One(descriptor) => descriptor.style.clone(),
         }),
+        font_feature_settings: OptionStringVc::cell(None),
         variable: OptionStringVc::cell(options.variable.clone()),
     }))
 }
@@ -5,8 +5,8 @@ use serde::{Deserialize, Serialize};
 use turbo_binding::turbo::tasks::{primitives::StringVc, trace::TraceRawVcs, Value};
 
 use super::request::{
-    AdjustFontFallback, NextFontLocalRequest, NextFontLocalRequestArguments, SrcDescriptor,
-    SrcRequest,
+    AdjustFontFallback, FontFaceSelector, NextFontLocalRequest, NextFontLocalRequestArguments,
+    SrcDescriptor, SrcRequest,
 };
 
 /// A normalized, Vc-friendly struct derived from validating and transforming
@@ -31,6 +31,11 @@ pub(super) struct NextFontLocalOptions {
     /// The name of the variable assigned to the results of calling the
     /// `localFont` function. This is used as the font family's base name.
     pub variable_name: String,
+    /// Whether the `font-family` chain ends with the generic font family of
+    /// the fallback font, e.g. `sans-serif`.
+    pub append_generic_font_family: bool,
+    /// See `NextFontGoogleOptions::max_fonts`
+    pub max_fonts: Option<usize>,
 }
 
 #[turbo_tasks::value_impl]
@@ -56,6 +61,8 @@ pub(super) struct FontDescriptor {
     pub style: Option<String>,
     pub path: String,
     pub ext: String,
+    /// The face to read metrics from if the file is a font collection
+    pub face: Option<FontFaceSelector>,
 }
 
 impl FontDescriptor {
@@ -75,6 +82,7 @@ impl FontDescriptor {
                 .and_then(|w| FontWeight::from_str(w).ok()),
             style: src_descriptor.style.clone(),
             ext,
+            face: src_descriptor.face.clone(),
         })
     }
 }
@@ -139,6 +147,9 @@ pub(super) fn options_from_request(request: &NextFontLocalRequest) -> Result<Nex
         src,
         adjust_font_fallback,
         variable,
+        face,
+        append_generic_font_family,
+        max_fonts,
     } = &request.arguments.0;
 
     let fonts = match src {
@@ -153,6 +164,7 @@ pub(super) fn options_from_request(request: &NextFontLocalRequest) -> Result<Nex
                 path: path.to_owned(),
                 weight: weight.to_owned(),
                 style: style.to_owned(),
+                face: face.to_owned(),
             })?)
         }
     };
@@ -167,6 +179,8 @@ pub(super) fn options_from_request(request: &NextFontLocalRequest) -> Result<Nex
         variable_name: request.variable_name.to_owned(),
         default_weight: weight.as_ref().and_then(|s| s.parse().ok()),
         default_style: style.to_owned(),
+        append_generic_font_family: *append_generic_font_family,
+        max_fonts: *max_fonts,
     })
 }
 
@@ -204,6 +218,7 @@ mod tests {
                     weight: None,
                     style: None,
                     ext: "ttf".to_owned(),
+                    face: None,
                 }),
                 default_style: None,
                 default_weight: None,
@@ -212,7 +227,9 @@ mod tests {
                 fallback: None,
                 adjust_font_fallback: AdjustFontFallback::Arial,
                 variable: None,
-                variable_name: "myFont".to_owned()
+                variable_name: "myFont".to_owned(),
+                append_generic_font_family: false,
+                max_fonts: None,
             },
         );
 
@@ -252,12 +269,14 @@ mod tests {
                         weight: Some(FontWeight::Fixed("400".to_owned())),
                         style: Some("normal".to_owned()),
                         ext: "ttf".to_owned(),
+                        face: None,
                     },
                     FontDescriptor {
                         path: "./Roboto-Italic.ttf".to_owned(),
                         weight: Some(FontWeight::Fixed("400".to_owned())),
                         style: None,
                         ext: "ttf".to_owned(),
+                        face: None,
                     }
                 ]),
                 default_weight: Some(FontWeight::Fixed("300".to_owned())),
@@ -267,7 +286,9 @@ mod tests {
                 fallback: None,
                 adjust_font_fallback: AdjustFontFallback::Arial,
                 variable: None,
-                variable_name: "myFont".to_owned()
+                variable_name: "myFont".to_owned(),
+                append_generic_font_family: false,
+                max_fonts: None,
             },
         );
 
@@ -332,6 +353,7 @@ mod tests {
                     weight: Some(FontWeight::Fixed("500".to_owned())),
                     style: Some("italic".to_owned()),
                     ext: "woff".to_owned(),
+                    face: None,
                 }),
                 default_style: Some("italic".to_owned()),
                 default_weight: Some(FontWeight::Fixed("500".to_owned())),
@@ -340,7 +362,9 @@ mod tests {
                 fallback: Some(vec!["Fallback".to_owned()]),
                 adjust_font_fallback: AdjustFontFallback::TimesNewRoman,
                 variable: Some("myvar".to_owned()),
-                variable_name: "myFont".to_owned()
+                variable_name: "myFont".to_owned(),
+                append_generic_font_family: false,
+                max_fonts: None,
             },
         );
 
@@ -28,6 +28,10 @@ pub(super) struct NextFontLocalRequestArguments {
     )]
     pub adjust_font_fallback: AdjustFontFallback,
     pub variable: Option<String>,
+    pub face: Option<FontFaceSelector>,
+    #[serde(default)]
+    pub append_generic_font_family: bool,
+    pub max_fonts: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,6 +46,18 @@ pub(super) struct SrcDescriptor {
     pub path: String,
     pub weight: Option<String>,
     pub style: Option<String>,
+    pub face: Option<FontFaceSelector>,
+}
+
+/// The face to use from a font collection (`.ttc`) file, either by its index
+/// in the collection or by its PostScript name
+#[derive(
+    Clone, Debug, Deserialize, Hash, Ord, PartialOrd, PartialEq, Eq, Serialize, TraceRawVcs,
+)]
+#[serde(untagged)]
+pub(super) enum FontFaceSelector {
+    Index(usize),
+    PostScriptName(String),
 }
 
 /// The user's desired fallback font
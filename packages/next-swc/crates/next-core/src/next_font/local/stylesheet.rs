@@ -1,16 +1,23 @@
 use anyhow::{bail, Result};
 use indoc::formatdoc;
-use turbo_binding::turbo::tasks::primitives::{StringVc, U32Vc};
+use turbo_binding::turbo::{
+    tasks::primitives::{OptionStringVc, StringVc, U32Vc},
+    tasks_fs::FileSystemPathVc,
+};
 
 use super::options::{FontDescriptors, NextFontLocalOptionsVc};
 use crate::next_font::{
     font_fallback::FontFallbacksVc,
     stylesheet::{build_fallback_definition, build_font_class_rules},
-    util::{get_scoped_font_family, FontCssPropertiesVc, FontFamilyType},
+    util::{
+        get_scoped_font_family, quote_font_family, FontCssPropertiesVc, FontFamilyQuoting,
+        FontFamilyType,
+    },
 };
 
 #[turbo_tasks::function]
 pub(super) async fn build_stylesheet(
+    context: FileSystemPathVc,
     options: NextFontLocalOptionsVc,
     fallbacks: FontFallbacksVc,
     css_properties: FontCssPropertiesVc,
@@ -29,7 +36,13 @@ pub(super) async fn build_stylesheet(
         {}
     "#,
         *build_font_face_definitions(scoped_font_family, options).await?,
-        (*build_fallback_definition(fallbacks).await?),
+        (*build_fallback_definition(
+            context,
+            fallbacks,
+            FontFamilyQuoting::AsNeeded.cell(),
+            OptionStringVc::cell(None),
+        )
+        .await?),
         *build_font_class_rules(css_properties).await?
     )))
 }
@@ -52,13 +65,13 @@ pub(super) async fn build_font_face_definitions(
         definitions.push_str(&formatdoc!(
             r#"
             @font-face {{
-                font-family: '{}';
+                font-family: {};
                 src: url('{}') format('{}');
                 font-display: {};
                 {}{}
             }}
         "#,
-            *scoped_font_family.await?,
+            quote_font_family(&scoped_font_family.await?, &FontFamilyQuoting::AsNeeded),
             &font.path,
             ext_to_format(&font.ext)?,
             options.display,
@@ -1,10 +1,13 @@
 use anyhow::Result;
 use turbo_binding::turbo::tasks::primitives::{StringVc, U32Vc};
 
-use super::options::NextFontLocalOptionsVc;
+use super::{options::NextFontLocalOptionsVc, request::AdjustFontFallback};
 use crate::next_font::{
     font_fallback::{FontFallback, FontFallbacksVc},
-    util::{get_scoped_font_family, FontFamilyType},
+    util::{
+        append_generic_font_family, get_scoped_font_family, quote_font_family, FontFamilyQuoting,
+        FontFamilyType,
+    },
 };
 
 /// Returns a string to be used as the `font-family` property in css.
@@ -14,20 +17,23 @@ pub(super) async fn build_font_family_string(
     font_fallbacks: FontFallbacksVc,
     request_hash: U32Vc,
 ) -> Result<StringVc> {
-    let mut font_families = vec![format!(
-        "'{}'",
-        *get_scoped_font_family(
+    let mut font_families = vec![quote_font_family(
+        &get_scoped_font_family(
             FontFamilyType::WebFont.cell(),
             options.font_family(),
             request_hash,
         )
-        .await?
+        .await?,
+        &FontFamilyQuoting::AsNeeded,
     )];
 
     for font_fallback in &*font_fallbacks.await? {
         match *font_fallback.await? {
             FontFallback::Automatic(fallback) => {
-                font_families.push(format!("'{}'", *fallback.await?.scoped_font_family.await?));
+                font_families.push(quote_font_family(
+                    &fallback.await?.scoped_font_family.await?,
+                    &FontFamilyQuoting::AsNeeded,
+                ));
             }
             FontFallback::Manual(fallbacks) => {
                 font_families.extend_from_slice(&fallbacks.await?);
@@ -36,5 +42,16 @@ pub(super) async fn build_font_family_string(
         }
     }
 
+    let options = options.await?;
+    if options.append_generic_font_family {
+        // Local fonts have no category, so follow the fallback font they are
+        // adjusted for
+        let generic_font_family = match options.adjust_font_fallback {
+            AdjustFontFallback::TimesNewRoman => "serif",
+            AdjustFontFallback::Arial | AdjustFontFallback::None => "sans-serif",
+        };
+        append_generic_font_family(&mut font_families, generic_font_family);
+    }
+
     Ok(StringVc::cell(font_families.join(", ")))
 }
@@ -1,84 +1,304 @@
 use anyhow::Result;
 use indoc::formatdoc;
-use turbo_binding::turbo::tasks::primitives::StringVc;
+use serde::Serialize;
+use turbo_binding::{
+    turbo::{
+        tasks::primitives::{OptionStringVc, StringVc},
+        tasks_fs::FileSystemPathVc,
+    },
+    turbopack::core::issue::IssueSeverity,
+};
 
 use super::{
-    font_fallback::{FontFallback, FontFallbacksVc},
-    util::FontCssPropertiesVc,
+    font_fallback::{
+        find_default_fallback_font, DefaultFallbackFont, FontAdjustment, FontFallback,
+        FontFallbacksVc,
+    },
+    issue::NextFontIssue,
+    util::{
+        quote_font_family, CssFormat, DescentSign, FontCssPropertiesVc, FontFamilyQuoting,
+        FontFamilyQuotingVc,
+    },
 };
 
-/// Builds `@font-face` stylesheet definition for a given FontFallback
+/// Builds `@font-face` stylesheet definition for a given FontFallback,
+/// limited to `unicode_range` if given. Warns at `context` about fallbacks
+/// exceeding their CSS byte budget.
 #[turbo_tasks::function]
-pub(crate) async fn build_fallback_definition(fallbacks: FontFallbacksVc) -> Result<StringVc> {
+pub(crate) async fn build_fallback_definition(
+    context: FileSystemPathVc,
+    fallbacks: FontFallbacksVc,
+    quoting: FontFamilyQuotingVc,
+    unicode_range: OptionStringVc,
+) -> Result<StringVc> {
+    let quoting = &*quoting.await?;
+    let unicode_range = &*unicode_range.await?;
     let mut res = "".to_owned();
     for fallback_vc in &*fallbacks.await? {
         if let FontFallback::Automatic(fallback) = &*fallback_vc.await? {
             let fallback = fallback.await?;
+            let mut css = "".to_owned();
 
-            let override_properties = match &fallback.adjustment {
+            let mut override_properties = match &fallback.adjustment {
                 None => "".to_owned(),
-                Some(adjustment) => formatdoc!(
-                    r#"
-                    ascent-override: {}%;
-                    descent-override: {}%;
-                    line-gap-override: {}%;
-                    size-adjust: {}%;
-                "#,
-                    format_fixed_percentage(adjustment.ascent),
-                    format_fixed_percentage(adjustment.descent.abs()),
-                    format_fixed_percentage(adjustment.line_gap),
-                    format_fixed_percentage(adjustment.size_adjust)
-                ),
+                Some(adjustment) => build_override_properties(adjustment),
             };
+            if fallback.debug_unadjusted_overrides && fallback.adjustment.is_some() {
+                if let Some(fallback_font) =
+                    find_default_fallback_font(&fallback.local_font_family.await?)
+                {
+                    override_properties
+                        .push_str(&build_unadjusted_overrides_comment(fallback_font));
+                }
+            }
+            // Describe the faces matched by both the adjusted and unadjusted rules
+            let mut face_properties = fallback
+                .font_weight_range
+                .map(|(min_weight, max_weight)| {
+                    build_font_weight_range_property(min_weight, max_weight)
+                })
+                .unwrap_or_default();
+            if let Some(unicode_range) = unicode_range {
+                face_properties.push_str(&build_unicode_range_property(unicode_range));
+            }
+            if let Some(named_instance) = &fallback.named_instance {
+                face_properties.push_str(&build_named_instance_property(named_instance));
+            }
+            override_properties.push_str(&face_properties);
+
+            let font_family = quote_font_family(&fallback.scoped_font_family.await?, quoting);
+            let local_font_family = &*fallback.local_font_family.await?;
+            let font_face = format_budgeted_fallback_font_face(
+                &font_family,
+                local_font_family,
+                override_properties,
+                fallback.css_byte_budget,
+            );
+            if let Some(byte_budget) = fallback
+                .css_byte_budget
+                .filter(|byte_budget| font_face.len() > *byte_budget as usize)
+            {
+                NextFontIssue {
+                    path: context,
+                    title: StringVc::cell(format!(
+                        "Fallback font `{}` exceeds its CSS byte budget",
+                        *fallback.scoped_font_family.await?
+                    )),
+                    description: StringVc::cell(format!(
+                        "Its `@font-face` rule takes {} bytes without any override descriptors, \
+                         more than the budget of {} bytes. Increase `fallback_css_byte_budget` to \
+                         keep the fallback adjusted.",
+                        font_face.len(),
+                        byte_budget
+                    )),
+                    severity: IssueSeverity::Warning.cell(),
+                }
+                .cell()
+                .as_issue()
+                .emit();
+            }
+            if fallback.size_adjust_supports_guard && fallback.adjustment.is_some() {
+                css.push_str(&format_budgeted_fallback_font_face(
+                    &font_family,
+                    local_font_family,
+                    face_properties,
+                    None,
+                ));
+                css.push_str(&wrap_in_size_adjust_supports_guard(&font_face));
+            } else {
+                css.push_str(&font_face);
+            }
 
-            res.push_str(&formatdoc!(
-                r#"
-                @font-face {{
-                    font-family: '{}';
-                    src: local("{}");
-                    {}
-                }}
-            "#,
-                fallback.scoped_font_family.await?,
-                fallback.local_font_family.await?,
-                override_properties
-            ));
+            if let Some(variable) = &fallback.variable {
+                css.push_str(&build_fallback_variable_rule(variable, &font_family));
+            }
+            if let (true, Some(adjustment)) =
+                (fallback.debug_custom_properties, &fallback.adjustment)
+            {
+                css.push_str(&build_override_custom_properties(
+                    &fallback.scoped_font_family.await?,
+                    adjustment,
+                ));
+            }
+            res.push_str(&match fallback.css_format {
+                Some(css_format) => format_css(&css, css_format),
+                None => css,
+            });
         }
     }
 
     Ok(StringVc::cell(res))
 }
 
+/// The `*-override` and `size-adjust` descriptors of a [[FontAdjustment]], from
+/// least to most impactful on layout
+const OVERRIDE_DESCRIPTORS_BY_IMPACT: &[&str] = &[
+    "line-gap-override",
+    "descent-override",
+    "ascent-override",
+    "size-adjust",
+];
+
+/// Renders the `@font-face` rule of an automatic fallback. When the rule takes
+/// more than `byte_budget` bytes, override descriptors are dropped from
+/// `override_properties` from least to most impactful, `line-gap-override`
+/// first, until it fits or none are left. Comments, like the one of
+/// [[build_unadjusted_overrides_comment]], are kept as they are.
+fn format_budgeted_fallback_font_face(
+    font_family: &str,
+    local_font_family: &str,
+    mut override_properties: String,
+    byte_budget: Option<u32>,
+) -> String {
+    let format_font_face = |override_properties: &str| {
+        formatdoc!(
+            r#"
+            @font-face {{
+                font-family: {};
+                src: local("{}");
+                {}
+            }}
+        "#,
+            font_family,
+            local_font_family,
+            override_properties
+        )
+    };
+
+    let mut font_face = format_font_face(&override_properties);
+    if let Some(byte_budget) = byte_budget {
+        for descriptor in OVERRIDE_DESCRIPTORS_BY_IMPACT {
+            if font_face.len() <= byte_budget as usize {
+                break;
+            }
+
+            override_properties = remove_declarations(&override_properties, descriptor);
+            font_face = format_font_face(&override_properties);
+        }
+    }
+
+    font_face
+}
+
+/// Removes the declarations of `property` from `declarations`, one per line,
+/// leaving the lines of comments untouched
+fn remove_declarations(declarations: &str, property: &str) -> String {
+    let mut in_comment = false;
+    let mut remaining = String::with_capacity(declarations.len());
+    for line in declarations.lines() {
+        let is_declaration = !in_comment && line.starts_with(&format!("{}:", property));
+        if line.starts_with("/*") {
+            in_comment = true;
+        }
+        if line.ends_with("*/") {
+            in_comment = false;
+        }
+        if !is_declaration {
+            remaining.push_str(line);
+            remaining.push('\n');
+        }
+    }
+    remaining
+}
+
+/// Lays out `css` generated here according to `css_format`. Expects one
+/// declaration, rule opening or closing brace per line, as generated.
+fn format_css(css: &str, css_format: CssFormat) -> String {
+    let mut formatted = "".to_owned();
+    let mut depth: usize = 0;
+    for line in css.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if line.starts_with('}') {
+            depth = depth.saturating_sub(1);
+        }
+        match css_format {
+            CssFormat::Pretty => {
+                formatted.push_str(&"    ".repeat(depth));
+                formatted.push_str(line);
+                formatted.push('\n');
+            }
+            CssFormat::Minified => match line.strip_suffix(" {") {
+                Some(prelude) => {
+                    formatted.push_str(prelude);
+                    formatted.push('{');
+                }
+                None => formatted.push_str(&line.replacen(": ", ":", 1)),
+            },
+        }
+        if line.ends_with('{') {
+            depth += 1;
+        }
+    }
+    formatted
+}
+
+/// Wraps the `@font-face` rule of an adjusted fallback in a `@supports` rule,
+/// so browsers ignoring `size-adjust` use an unadjusted fallback declared
+/// before it instead.
+fn wrap_in_size_adjust_supports_guard(font_face: &str) -> String {
+    format!("@supports (size-adjust: 100%) {{\n{}}}\n", font_face)
+}
+
+/// Declares `header` at the top of `css` in a `/*! */` comment, which CSS
+/// minifiers keep, e.g. for license notices. `*/` in `header` is escaped so it
+/// can't end the comment early.
+pub(super) fn prepend_css_header(css: String, header: Option<&str>) -> String {
+    let Some(header) = header else {
+        return css;
+    };
+
+    let mut res = "/*!\n".to_owned();
+    for line in header.replace("*/", "*\\/").lines() {
+        res.push_str(format!(" * {}", line).trim_end());
+        res.push('\n');
+    }
+    res.push_str(" */\n");
+    res.push_str(&css);
+    res
+}
+
 #[turbo_tasks::function]
 pub(super) async fn build_font_class_rules(
     css_properties: FontCssPropertiesVc,
 ) -> Result<StringVc> {
     let css_properties = &*css_properties.await?;
-    let font_family_string = &*css_properties.font_family.await?;
 
+    Ok(StringVc::cell(format_font_class_rules(
+        &css_properties.font_family.await?,
+        css_properties.weight.await?.as_deref(),
+        css_properties.style.await?.as_deref(),
+        css_properties.font_feature_settings.await?.as_deref(),
+        css_properties.variable.await?.as_deref(),
+    )))
+}
+
+/// The synchronous implementation of [[build_font_class_rules]]
+fn format_font_class_rules(
+    font_family: &str,
+    weight: Option<&str>,
+    style: Option<&str>,
+    font_feature_settings: Option<&str>,
+    variable: Option<&str>,
+) -> String {
     let mut rules = formatdoc!(
         r#"
         .className {{
             font-family: {};
-            {}{}
+            {}{}{}
         }}
     "#,
-        font_family_string,
-        css_properties
-            .weight
-            .await?
-            .as_ref()
+        font_family,
+        weight
             .map(|w| format!("font-weight: {};\n", w))
             .unwrap_or_else(|| "".to_owned()),
-        css_properties
-            .style
-            .await?
-            .as_ref()
+        style
             .map(|s| format!("font-style: {};\n", s))
             .unwrap_or_else(|| "".to_owned()),
+        font_feature_settings
+            .map(|f| format!("font-feature-settings: {};\n", f))
+            .unwrap_or_else(|| "".to_owned()),
     );
 
-    if let Some(variable) = &*css_properties.variable.await? {
+    if let Some(variable) = variable {
         rules.push_str(&formatdoc!(
             r#"
         .variable {{
@@ -86,13 +306,541 @@ pub(super) async fn build_font_class_rules(
         }}
         "#,
             variable,
-            font_family_string
+            font_family
         ))
     }
 
-    Ok(StringVc::cell(rules))
+    rules
+}
+
+/// Renders the `*-override` and `size-adjust` descriptors for a
+/// [[FontAdjustment]], e.g. `ascent-override: 56.8%;`
+pub(crate) fn build_override_properties(adjustment: &FontAdjustment) -> String {
+    let (ascent, descent, line_gap, size_adjust) = adjustment.override_percentages();
+    formatdoc!(
+        r#"
+        ascent-override: {}%;
+        descent-override: {}%;
+        line-gap-override: {}%;
+        size-adjust: {}%;
+    "#,
+        format_fixed_percentage(ascent),
+        format_fixed_percentage(descent),
+        format_fixed_percentage(line_gap),
+        format_fixed_percentage(size_adjust)
+    )
+}
+
+/// Renders the override values of `fallback_font` without adjustment as a
+/// comment, e.g. `/* Without adjustment: ascent-override: 90.53%; ... */`
+fn build_unadjusted_overrides_comment(fallback_font: &DefaultFallbackFont) -> String {
+    format!(
+        "/* Without adjustment:\n{}*/\n",
+        build_override_properties(&fallback_font.unadjusted())
+    )
+}
+
+/// Renders the `font-weight` descriptor of a face spanning a range of weights,
+/// e.g. `font-weight: 400 700;`
+fn build_font_weight_range_property(min_weight: u16, max_weight: u16) -> String {
+    format!("font-weight: {} {};\n", min_weight, max_weight)
+}
+
+/// Renders the `unicode-range` descriptor, e.g. `unicode-range: U+0370-03FF;`
+fn build_unicode_range_property(unicode_range: &str) -> String {
+    format!("unicode-range: {};\n", unicode_range)
+}
+
+/// Renders the `font-named-instance` descriptor selecting a named instance of
+/// a variable font, e.g. `font-named-instance: 'Bold';`
+fn build_named_instance_property(named_instance: &str) -> String {
+    format!(
+        "font-named-instance: {};\n",
+        quote_font_family(named_instance, &FontFamilyQuoting::Always)
+    )
+}
+
+/// Assigns the scoped `font_family` of a fallback to the css custom property
+/// `variable` in the `variable` class, as [[build_font_class_rules]] does for
+/// the webfont, so stylesheets referring to fonts by css variables can use the
+/// fallback on its own.
+fn build_fallback_variable_rule(variable: &str, font_family: &str) -> String {
+    formatdoc!(
+        r#"
+        .variable {{
+            {}: {};
+        }}
+    "#,
+        variable,
+        font_family
+    )
+}
+
+/// Renders the values of [[build_override_properties]] as custom properties on
+/// `:root`, named after the scoped font family without its hash, e.g.
+/// `--inter-fallback-ascent: 93.24%;` for `__Inter_Fallback_c123b8`.
+pub(crate) fn build_override_custom_properties(
+    scoped_font_family: &str,
+    adjustment: &FontAdjustment,
+) -> String {
+    let name = scoped_font_family.trim_start_matches('_');
+    let name = match name.rsplit_once('_') {
+        Some((name, _hash)) => name,
+        None => name,
+    };
+    let prefix = name.to_lowercase().replace('_', "-");
+    let (ascent, descent, line_gap, size_adjust) = adjustment.override_percentages();
+
+    formatdoc!(
+        r#"
+        :root {{
+            --{prefix}-ascent: {}%;
+            --{prefix}-descent: {}%;
+            --{prefix}-line-gap: {}%;
+            --{prefix}-size-adjust: {}%;
+        }}
+    "#,
+        format_fixed_percentage(ascent),
+        format_fixed_percentage(descent),
+        format_fixed_percentage(line_gap),
+        format_fixed_percentage(size_adjust),
+        prefix = prefix,
+    )
+}
+
+/// An automatic fallback in the shape CSS-in-JS libraries expect for props,
+/// e.g. to pass to a styled component: `{"fontFamily": "...", "style": {...}}`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CssInJsFallback {
+    pub font_family: String,
+    pub style: CssInJsFallbackStyle,
+}
+
+/// The override properties of a [[CssInJsFallback]], as CSS percentages. The
+/// sign of `descent_override` follows the [[DescentSign]] the fallback was
+/// created with.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CssInJsFallbackStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ascent_override: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub descent_override: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_gap_override: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_adjust: Option<String>,
 }
 
-fn format_fixed_percentage(value: f64) -> String {
-    format!("{:.2}", value * 100.0)
+impl CssInJsFallback {
+    pub(crate) fn new(
+        scoped_font_family: &str,
+        adjustment: Option<&FontAdjustment>,
+        descent_sign: DescentSign,
+        quoting: &FontFamilyQuoting,
+    ) -> Self {
+        let percentage = |value: f64| Some(format!("{}%", format_fixed_percentage(value)));
+
+        CssInJsFallback {
+            font_family: quote_font_family(scoped_font_family, quoting),
+            style: match adjustment {
+                None => CssInJsFallbackStyle::default(),
+                Some(adjustment) => {
+                    let (ascent, descent, line_gap, size_adjust) =
+                        adjustment.override_percentages();
+                    CssInJsFallbackStyle {
+                        ascent_override: percentage(ascent),
+                        descent_override: percentage(match descent_sign {
+                            DescentSign::Absolute => descent,
+                            DescentSign::Signed => descent.copysign(adjustment.descent),
+                        }),
+                        line_gap_override: percentage(line_gap),
+                        size_adjust: percentage(size_adjust),
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Formats a percentage, e.g. one of [[FontAdjustment::override_percentages]],
+/// with two decimals
+fn format_fixed_percentage(percentage: f64) -> String {
+    format_css_number(percentage, 2)
+}
+
+/// Formats `value` as a CSS number with `decimals` digits after a `.` decimal
+/// separator. Rust's float formatting never consults the system locale, so the
+/// output is the same on every machine.
+pub(crate) fn format_css_number(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value);
+    // Avoid emitting `-0.00` for tiny negative values
+    let formatted = match formatted.strip_prefix('-') {
+        Some(unsigned) if unsigned.chars().all(|c| c == '0' || c == '.') => unsigned.to_owned(),
+        _ => formatted,
+    };
+    debug_assert!(
+        is_css_number(&formatted),
+        "`{}` is not a valid CSS number",
+        formatted
+    );
+    formatted
+}
+
+/// Whether `value` is a CSS `<number>` in plain decimal notation, e.g. `-1.5`,
+/// as opposed to e.g. `NaN` or `inf`, which browsers reject.
+fn is_css_number(value: &str) -> bool {
+    let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    match fraction {
+        Some(fraction) => is_digits(fraction) && (integer.is_empty() || is_digits(integer)),
+        None => is_digits(integer),
+    }
+}
+
+/// Whether `value` is a CSS `<percentage>` such as `93.24%`, see
+/// [[is_css_number]].
+#[cfg(test)]
+fn is_css_percentage(value: &str) -> bool {
+    value.strip_suffix('%').map_or(false, is_css_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use indoc::indoc;
+
+    use super::{
+        build_fallback_variable_rule, build_font_weight_range_property,
+        build_named_instance_property, build_override_custom_properties, build_override_properties,
+        build_unadjusted_overrides_comment, build_unicode_range_property,
+        format_budgeted_fallback_font_face, format_css, format_css_number, format_fixed_percentage,
+        format_font_class_rules, is_css_percentage, prepend_css_header,
+        wrap_in_size_adjust_supports_guard, CssInJsFallback,
+    };
+    use crate::next_font::{
+        font_fallback::{tests::INTER_ADJUSTMENT, DEFAULT_SANS_SERIF_FONT},
+        util::{CssFormat, DescentSign, FontFamilyQuoting},
+    };
+
+    #[test]
+    fn test_formats_numbers_with_a_dot_separator() {
+        assert_eq!(format_css_number(1234.5678, 2), "1234.57");
+        assert_eq!(format_css_number(0.5, 3), "0.500");
+        assert_eq!(format_fixed_percentage(93.24334770490376), "93.24");
+        assert_eq!(format_fixed_percentage(-0.0001), "0.00");
+        assert!(!format_fixed_percentage(103.89481114147647).contains(','));
+    }
+
+    #[test]
+    fn test_builds_override_custom_properties() {
+        let adjustment = INTER_ADJUSTMENT;
+
+        assert_eq!(
+            build_override_custom_properties("__Inter_Fallback_c123b8", &adjustment),
+            ":root {\n    --inter-fallback-ascent: 93.24%;\n    --inter-fallback-descent: \
+             23.24%;\n    --inter-fallback-line-gap: 0.00%;\n    --inter-fallback-size-adjust: \
+             103.89%;\n}\n"
+        );
+        assert!(
+            build_override_custom_properties("__Roboto_Slab_Fallback_d456e9", &adjustment)
+                .contains("--roboto-slab-fallback-ascent: 93.24%;")
+        );
+    }
+
+    #[test]
+    fn test_serializes_css_in_js_fallback() -> Result<()> {
+        let adjustment = INTER_ADJUSTMENT;
+
+        assert_eq!(
+            serde_json::to_value(CssInJsFallback::new(
+                "__Inter_Fallback_c123b8",
+                Some(&adjustment),
+                DescentSign::Absolute,
+                &FontFamilyQuoting::Always,
+            ))?,
+            serde_json::json!({
+                "fontFamily": "'__Inter_Fallback_c123b8'",
+                "style": {
+                    "ascentOverride": "93.24%",
+                    "descentOverride": "23.24%",
+                    "lineGapOverride": "0.00%",
+                    "sizeAdjust": "103.89%",
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(CssInJsFallback::new(
+                "__Inter_Fallback_c123b8",
+                None,
+                DescentSign::Absolute,
+                &FontFamilyQuoting::AsNeeded,
+            ))?,
+            serde_json::json!({
+                "fontFamily": "__Inter_Fallback_c123b8",
+                "style": {},
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_builds_font_weight_range_property() {
+        assert_eq!(
+            build_font_weight_range_property(400, 700),
+            "font-weight: 400 700;\n"
+        );
+    }
+
+    #[test]
+    fn test_builds_unicode_range_property() {
+        assert_eq!(
+            build_unicode_range_property("U+0000-00FF, U+0131, U+0152-0153"),
+            "unicode-range: U+0000-00FF, U+0131, U+0152-0153;\n"
+        );
+    }
+
+    #[test]
+    fn test_override_properties_are_css_percentages() {
+        let override_properties = build_override_properties(&INTER_ADJUSTMENT);
+
+        let values: Vec<&str> = override_properties
+            .lines()
+            .filter_map(|line| line.split_once(": "))
+            .map(|(_, value)| value.trim_end_matches(';'))
+            .collect();
+        assert_eq!(values, ["93.24%", "23.24%", "0.00%", "103.89%"]);
+        assert!(values.iter().all(|value| is_css_percentage(value)));
+
+        for invalid in [
+            "NaN%", "inf%", "93.24", "93.24%px", "93.%", "%", "1e3%", "--1%",
+        ] {
+            assert!(!is_css_percentage(invalid), "{} should be invalid", invalid);
+        }
+        assert!(is_css_percentage("-.5%"));
+    }
+
+    #[test]
+    fn test_preserves_font_feature_settings() {
+        let rules = format_font_class_rules(
+            "__Inter_c123b8, __Inter_Fallback_c123b8, sans-serif",
+            Some("400"),
+            None,
+            Some(r#""ss01", "liga" 0"#),
+            None,
+        );
+
+        assert!(rules.contains("font-weight: 400;\n"));
+        assert!(rules.contains(r#"font-feature-settings: "ss01", "liga" 0;"#));
+        assert!(
+            !format_font_class_rules("__Inter_c123b8", None, None, None, None)
+                .contains("font-feature-settings")
+        );
+    }
+
+    #[test]
+    fn test_builds_unadjusted_overrides_comment() {
+        assert_eq!(
+            build_unadjusted_overrides_comment(&DEFAULT_SANS_SERIF_FONT),
+            "/* Without adjustment:\nascent-override: 90.53%;\ndescent-override: \
+             21.19%;\nline-gap-override: 3.27%;\nsize-adjust: 100.00%;\n*/\n"
+        );
+    }
+
+    #[test]
+    fn test_drops_least_impactful_overrides_to_fit_byte_budget() {
+        let override_properties = build_override_properties(&INTER_ADJUSTMENT);
+        let font_face = |byte_budget| {
+            format_budgeted_fallback_font_face(
+                "__Inter_Fallback_c123b8",
+                "Arial",
+                override_properties.clone(),
+                byte_budget,
+            )
+        };
+
+        let unbudgeted = font_face(None);
+        assert_eq!(font_face(Some(unbudgeted.len() as u32)), unbudgeted);
+
+        let budgeted = font_face(Some(unbudgeted.len() as u32 - 1));
+        assert!(!budgeted.contains("line-gap-override"));
+        assert!(budgeted.contains("descent-override: 23.24%;"));
+        assert!(budgeted.contains("ascent-override: 93.24%;"));
+        assert!(budgeted.contains("size-adjust: 103.89%;"));
+        assert!(budgeted.len() < unbudgeted.len());
+
+        assert!(!font_face(Some(0)).contains("size-adjust"));
+    }
+
+    #[test]
+    fn test_keeps_unadjusted_overrides_comment_within_byte_budget() {
+        let comment = build_unadjusted_overrides_comment(&DEFAULT_SANS_SERIF_FONT);
+        let font_face = format_budgeted_fallback_font_face(
+            "__Inter_Fallback_c123b8",
+            "Arial",
+            format!(
+                "{}{}",
+                build_override_properties(&INTER_ADJUSTMENT),
+                comment
+            ),
+            Some(0),
+        );
+
+        assert!(!font_face.contains("size-adjust: 103.89%;"));
+        assert!(font_face.contains(&comment));
+    }
+
+    #[test]
+    fn test_wraps_adjusted_fallback_in_supports_guard() {
+        let override_properties = build_override_properties(&INTER_ADJUSTMENT);
+        let unadjusted = format_budgeted_fallback_font_face(
+            "__Inter_Fallback_c123b8",
+            "Arial",
+            "".to_owned(),
+            None,
+        );
+        let adjusted = format_budgeted_fallback_font_face(
+            "__Inter_Fallback_c123b8",
+            "Arial",
+            override_properties,
+            None,
+        );
+        let guarded = format!(
+            "{}{}",
+            unadjusted,
+            wrap_in_size_adjust_supports_guard(&adjusted)
+        );
+
+        let (outside, inside) = guarded
+            .split_once("@supports (size-adjust: 100%) {\n")
+            .expect("Expected a @supports rule");
+        assert_eq!(outside, unadjusted);
+        assert!(outside.contains("src: local(\"Arial\");"));
+        assert!(!outside.contains("size-adjust"));
+        assert_eq!(inside, format!("{}}}\n", adjusted));
+        assert!(inside.contains("size-adjust: 103.89%;"));
+    }
+
+    #[test]
+    fn test_prepends_css_header() {
+        let font_face = format_budgeted_fallback_font_face(
+            "__Inter_Fallback_c123b8",
+            "Arial",
+            "size-adjust: 103.89%;\n".to_owned(),
+            None,
+        );
+
+        assert_eq!(
+            prepend_css_header(
+                font_face.clone(),
+                Some("Inter is licensed under the OFL.\n\nSee https://openfontlicense.org */")
+            ),
+            format!(
+                "{}{}",
+                indoc! {"
+                    /*!
+                     * Inter is licensed under the OFL.
+                     *
+                     * See https://openfontlicense.org *\\/
+                     */
+                "},
+                font_face
+            )
+        );
+        assert_eq!(prepend_css_header(font_face.clone(), None), font_face);
+    }
+
+    #[test]
+    fn test_renders_descent_sign_convention() {
+        let adjustment = INTER_ADJUSTMENT;
+
+        let descent_override = |descent_sign| {
+            CssInJsFallback::new(
+                "__Inter_Fallback_c123b8",
+                Some(&adjustment),
+                descent_sign,
+                &FontFamilyQuoting::Always,
+            )
+            .style
+            .descent_override
+        };
+
+        assert_eq!(
+            descent_override(DescentSign::Absolute).as_deref(),
+            Some("23.24%")
+        );
+        assert_eq!(
+            descent_override(DescentSign::Signed).as_deref(),
+            Some("-23.24%")
+        );
+        // Negative descriptors are invalid CSS, so the CSS is always positive
+        assert!(build_override_properties(&adjustment).contains("descent-override: 23.24%;"));
+    }
+
+    #[test]
+    fn test_assigns_fallback_font_family_to_variable() {
+        assert_eq!(
+            build_fallback_variable_rule("--font-inter-fallback", "__Inter_Fallback_c123b8"),
+            ".variable {\n    --font-inter-fallback: __Inter_Fallback_c123b8;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_builds_named_instance_property() {
+        assert_eq!(
+            build_named_instance_property("Bold"),
+            "font-named-instance: 'Bold';\n"
+        );
+        assert_eq!(
+            build_named_instance_property("Semi'Bold"),
+            "font-named-instance: 'Semi\\'Bold';\n"
+        );
+    }
+
+    #[test]
+    fn test_formats_fallback_css() {
+        let font_face = format_budgeted_fallback_font_face(
+            "__Inter_Fallback_c123b8",
+            "Arial",
+            build_override_properties(&INTER_ADJUSTMENT),
+            None,
+        );
+        let css = wrap_in_size_adjust_supports_guard(&font_face);
+
+        assert_eq!(
+            format_css(&css, CssFormat::Pretty),
+            indoc! {r#"
+                @supports (size-adjust: 100%) {
+                    @font-face {
+                        font-family: __Inter_Fallback_c123b8;
+                        src: local("Arial");
+                        ascent-override: 93.24%;
+                        descent-override: 23.24%;
+                        line-gap-override: 0.00%;
+                        size-adjust: 103.89%;
+                    }
+                }
+            "#}
+        );
+        assert_eq!(
+            format_css(&css, CssFormat::Minified),
+            "@supports (size-adjust: \
+             100%){@font-face{font-family:__Inter_Fallback_c123b8;src:local(\"Arial\");\
+             ascent-override:93.24%;descent-override:23.24%;line-gap-override:0.00%;size-adjust:\
+             103.89%;}}"
+        );
+        // Formatting is idempotent
+        let pretty = format_css(&css, CssFormat::Pretty);
+        assert_eq!(format_css(&pretty, CssFormat::Pretty), pretty);
+        assert_eq!(
+            format_css(&pretty, CssFormat::Minified),
+            format_css(&css, CssFormat::Minified)
+        );
+    }
 }
@@ -1,8 +1,17 @@
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use turbo_binding::{
     turbo::tasks_hash::hash_xxh3_hash64, turbopack::core::resolve::pattern::QueryMapVc,
 };
-use turbo_tasks::primitives::{OptionStringVc, StringVc, U32Vc};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc, U32Vc},
+    trace::TraceRawVcs,
+};
 
 /// CSS properties and values for a given font variation. These are rendered as
 /// values in both the returned JavaScript object and in the referenced css
@@ -12,6 +21,8 @@ pub(crate) struct FontCssProperties {
     pub font_family: StringVc,
     pub weight: OptionStringVc,
     pub style: OptionStringVc,
+    /// A `font-feature-settings` value to preserve in the className rules
+    pub font_feature_settings: OptionStringVc,
     pub variable: OptionStringVc,
 }
 
@@ -35,6 +46,12 @@ pub(crate) async fn get_request_hash(query_vc: QueryMapVc) -> Result<U32Vc> {
     ))
 }
 
+/// Mixes the `version` of a font into a `request_hash`, so names scoped with
+/// the result change when the font does.
+pub(crate) fn get_versioned_request_hash(request_hash: u32, version: &str) -> u32 {
+    hash_xxh3_hash64((request_hash, version)) as u32
+}
+
 #[turbo_tasks::value(shared)]
 pub(crate) enum FontFamilyType {
     WebFont,
@@ -52,19 +69,159 @@ pub(crate) async fn get_scoped_font_family(
     font_family_name: StringVc,
     request_hash: U32Vc,
 ) -> Result<StringVc> {
+    Ok(StringVc::cell(format_scoped_font_family(
+        &*ty.await?,
+        &font_family_name.await?,
+        *request_hash.await?,
+    )))
+}
+
+/// The synchronous implementation of [[get_scoped_font_family]].
+pub(crate) fn format_scoped_font_family(
+    ty: &FontFamilyType,
+    font_family_name: &str,
+    request_hash: u32,
+) -> String {
     let hash = {
-        let mut hash = format!("{:x?}", request_hash.await?);
+        let mut hash = format!("{:x?}", request_hash);
         hash.truncate(6);
         hash
     };
 
-    let font_family_base = font_family_name.await?.replace(' ', "_");
-    let font_family_name = match &*ty.await? {
+    let font_family_base = font_family_name.replace(' ', "_");
+    let font_family_name = match ty {
         FontFamilyType::WebFont => font_family_base,
         FontFamilyType::Fallback => format!("{}_Fallback", font_family_base),
     };
 
-    Ok(StringVc::cell(format!("__{}_{}", font_family_name, hash)))
+    format!("__{}_{}", font_family_name, hash)
+}
+
+/// How font family names are quoted in generated CSS.
+#[turbo_tasks::value(shared)]
+pub(crate) enum FontFamilyQuoting {
+    /// Quote only names that aren't a single CSS identifier, as recommended
+    /// for names with whitespace, punctuation or that collide with keywords.
+    AsNeeded,
+    /// Always quote names.
+    Always,
+}
+
+/// How whitespace is laid out in generated CSS.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, TraceRawVcs,
+)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CssFormat {
+    /// One declaration per line, indented by nesting, for development.
+    Pretty,
+    /// Without line breaks, indentation or optional spaces, for production.
+    Minified,
+}
+
+/// How the sign of `descent-override` is rendered in generated CSS.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum DescentSign {
+    /// Render the descent as a positive percentage, as CSS expects.
+    #[default]
+    Absolute,
+    /// Keep the sign of the font's descent, which is usually negative, for CSS
+    /// generators that handle it themselves.
+    Signed,
+}
+
+/// Keywords that a family name must be quoted to be distinguished from.
+static FONT_FAMILY_KEYWORDS: &[&str] = &[
+    "cursive",
+    "default",
+    "emoji",
+    "fangsong",
+    "fantasy",
+    "inherit",
+    "initial",
+    "math",
+    "monospace",
+    "revert",
+    "sans-serif",
+    "serif",
+    "system-ui",
+    "ui-monospace",
+    "ui-rounded",
+    "ui-sans-serif",
+    "ui-serif",
+    "unset",
+];
+
+/// Renders `font_family_name` for use in a `font-family` property or
+/// descriptor, quoting it according to `quoting`.
+pub(crate) fn quote_font_family(font_family_name: &str, quoting: &FontFamilyQuoting) -> String {
+    let needs_quotes = match quoting {
+        FontFamilyQuoting::Always => true,
+        FontFamilyQuoting::AsNeeded => {
+            !is_css_identifier(font_family_name)
+                || FONT_FAMILY_KEYWORDS
+                    .iter()
+                    .any(|keyword| keyword.eq_ignore_ascii_case(font_family_name))
+        }
+    };
+
+    if needs_quotes {
+        format!(
+            "'{}'",
+            font_family_name.replace('\\', "\\\\").replace('\'', "\\'")
+        )
+    } else {
+        font_family_name.to_owned()
+    }
+}
+
+/// Appends `generic_family`, e.g. `sans-serif`, as the last font family of a
+/// `font-family` chain, removing it if it is already elsewhere in the chain.
+pub(crate) fn append_generic_font_family(font_families: &mut Vec<String>, generic_family: &str) {
+    font_families.retain(|font_family| !font_family.trim().eq_ignore_ascii_case(generic_family));
+    font_families.push(generic_family.to_owned());
+}
+
+/// Whether `value` is a single CSS identifier that needs no escaping, e.g.
+/// `Arial` or `__Inter_Fallback_c123b8`.
+fn is_css_identifier(value: &str) -> bool {
+    let is_name_start = |c: char| c.is_ascii_alphabetic() || c == '_' || !c.is_ascii();
+    let is_name = |c: char| is_name_start(c) || c.is_ascii_digit() || c == '-';
+
+    let unprefixed = value.strip_prefix('-').unwrap_or(value);
+    let mut chars = unprefixed.chars();
+    match chars.next() {
+        Some(c) if is_name_start(c) || (c == '-' && value != "--") => chars.all(is_name),
+        _ => false,
+    }
+}
+
+/// Whether `value` is the name of a css custom property (css variable), e.g.
+/// `--font-inter`, that can be declared without escaping.
+pub(crate) fn is_css_custom_property_name(value: &str) -> bool {
+    match value.strip_prefix("--") {
+        Some(name) => {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii())
+        }
+        None => false,
+    }
 }
 
 /// Returns a [[StringVc]] uniquely identifying the request for the font.
@@ -76,3 +233,164 @@ pub async fn get_request_id(font_family: StringVc, request_hash: U32Vc) -> Resul
         request_hash.await?
     )))
 }
+
+/// A `f64` that can be used in font options. Options are turbo-tasks inputs and
+/// must implement `Eq`, `Ord` and `Hash`, which plain floats do not. Values are
+/// compared and hashed by their bit representation.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, TraceRawVcs)]
+#[serde(transparent)]
+pub(crate) struct OrderedF64(pub f64);
+
+impl PartialEq for OrderedF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for OrderedF64 {}
+
+impl PartialOrd for OrderedF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for OrderedF64 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        append_generic_font_family, format_scoped_font_family, get_versioned_request_hash,
+        is_css_custom_property_name, quote_font_family, FontFamilyQuoting, FontFamilyType,
+    };
+
+    #[test]
+    fn test_quotes_font_family_as_needed() {
+        assert_eq!(
+            quote_font_family("Times New Roman", &FontFamilyQuoting::AsNeeded),
+            "'Times New Roman'"
+        );
+        assert_eq!(
+            quote_font_family("Arial", &FontFamilyQuoting::AsNeeded),
+            "Arial"
+        );
+        assert_eq!(
+            quote_font_family("__Inter_Fallback_c123b8", &FontFamilyQuoting::AsNeeded),
+            "__Inter_Fallback_c123b8"
+        );
+        assert_eq!(
+            quote_font_family("serif", &FontFamilyQuoting::AsNeeded),
+            "'serif'"
+        );
+        assert_eq!(
+            quote_font_family("3Dumb", &FontFamilyQuoting::AsNeeded),
+            "'3Dumb'"
+        );
+        assert_eq!(
+            quote_font_family("Tom's Font", &FontFamilyQuoting::AsNeeded),
+            "'Tom\\'s Font'"
+        );
+    }
+
+    #[test]
+    fn test_always_quotes_font_family() {
+        assert_eq!(
+            quote_font_family("Times New Roman", &FontFamilyQuoting::Always),
+            "'Times New Roman'"
+        );
+        assert_eq!(
+            quote_font_family("Arial", &FontFamilyQuoting::Always),
+            "'Arial'"
+        );
+    }
+
+    #[test]
+    fn test_validates_css_custom_property_names() {
+        assert!(is_css_custom_property_name("--font-inter-fallback"));
+        assert!(is_css_custom_property_name("--_font2"));
+        assert!(!is_css_custom_property_name("--"));
+        assert!(!is_css_custom_property_name("font-inter"));
+        assert!(!is_css_custom_property_name(
+            "--x: red; } body { color: red"
+        ));
+    }
+
+    #[test]
+    fn test_appends_generic_font_family() {
+        let mut font_families = vec!["'__Inter_c123b8'".to_owned(), "Arial".to_owned()];
+        append_generic_font_family(&mut font_families, "sans-serif");
+        assert_eq!(
+            font_families,
+            vec!["'__Inter_c123b8'", "Arial", "sans-serif"]
+        );
+
+        let mut font_families = vec![
+            "'__Lora_c123b8'".to_owned(),
+            "serif".to_owned(),
+            "Georgia".to_owned(),
+        ];
+        append_generic_font_family(&mut font_families, "serif");
+        assert_eq!(font_families, vec!["'__Lora_c123b8'", "Georgia", "serif"]);
+
+        // A quoted family named like a keyword is not the keyword
+        let mut font_families = vec!["'serif'".to_owned()];
+        append_generic_font_family(&mut font_families, "serif");
+        assert_eq!(font_families, vec!["'serif'", "serif"]);
+    }
+
+    #[test]
+    fn test_scoped_fallback_font_family_is_distinct_from_webfont() {
+        for (font_family_name, request_hash) in [
+            ("Inter", 0xc123b8),
+            ("Roboto Slab", 0xd456e9),
+            ("Inter_Fallback", 0),
+        ] {
+            let webfont =
+                format_scoped_font_family(&FontFamilyType::WebFont, font_family_name, request_hash);
+            let fallback = format_scoped_font_family(
+                &FontFamilyType::Fallback,
+                font_family_name,
+                request_hash,
+            );
+
+            assert_ne!(webfont, fallback);
+            assert_eq!(
+                fallback,
+                format_scoped_font_family(
+                    &FontFamilyType::Fallback,
+                    font_family_name,
+                    request_hash
+                )
+            );
+        }
+        assert_eq!(
+            format_scoped_font_family(&FontFamilyType::Fallback, "Roboto Slab", 0xd456e9),
+            "__Roboto_Slab_Fallback_d456e9"
+        );
+    }
+
+    #[test]
+    fn test_scoped_font_family_changes_with_version() {
+        let scoped_font_family = |version: &str| {
+            format_scoped_font_family(
+                &FontFamilyType::Fallback,
+                "Inter",
+                get_versioned_request_hash(0xc123b8, version),
+            )
+        };
+
+        assert_ne!(scoped_font_family("3.019"), scoped_font_family("4.0"));
+        assert_eq!(scoped_font_family("3.019"), scoped_font_family("3.019"));
+        assert!(scoped_font_family("4.0").starts_with("__Inter_Fallback_"));
+    }
+}
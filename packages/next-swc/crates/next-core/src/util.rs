@@ -2,7 +2,9 @@ use anyhow::{anyhow, bail, Context, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use swc_core::ecma::ast::Program;
 use turbo_binding::{
-    turbo::tasks_fs::{json::parse_json_rope_with_source_context, FileContent, FileSystemPathVc},
+    turbo::tasks_fs::{
+        json::parse_json_rope_with_source_context, rope::Rope, FileContent, FileSystemPathVc,
+    },
     turbopack::{
         core::{
             asset::{Asset, AssetVc},
@@ -315,6 +317,16 @@ fn parse_config_from_js_value(module_asset: AssetVc, value: &JsValue) -> NextSou
 pub async fn load_next_json<T: DeserializeOwned>(
     context: FileSystemPathVc,
     path: &str,
+) -> Result<T> {
+    load_next_file(context, path, parse_json_rope_with_source_context).await
+}
+
+/// Resolves `path` in the `next` package like [[load_next_json]], parsing its
+/// content with `parse`, e.g. to only parse part of it.
+pub async fn load_next_file<T>(
+    context: FileSystemPathVc,
+    path: &str,
+    parse: impl FnOnce(&Rope) -> Result<T>,
 ) -> Result<T> {
     let request = RequestVc::module(
         "next".to_owned(),
@@ -352,9 +364,7 @@ pub async fn load_next_json<T: DeserializeOwned>(
         bail!("Expected file content for metrics data");
     };
 
-    let result: T = parse_json_rope_with_source_context(file.content())?;
-
-    Ok(result)
+    parse(file.content())
 }
 
 #[turbo_tasks::function]
@@ -73,6 +73,12 @@ pub struct DevServerOptions {
     #[cfg_attr(feature = "serializable", serde(default))]
     pub log_detail: bool,
 
+    /// Write issues to stderr as single-line JSON objects, for tooling that
+    /// scrapes the output.
+    #[cfg_attr(feature = "cli", clap(long))]
+    #[cfg_attr(feature = "serializable", serde(default))]
+    pub json_diagnostics: bool,
+
     /// Whether to enable full task stats recording in Turbo Engine.
     #[cfg_attr(feature = "cli", clap(long))]
     #[cfg_attr(feature = "serializable", serde(default))]
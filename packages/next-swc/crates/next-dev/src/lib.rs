@@ -24,6 +24,7 @@ use next_core::{
     create_web_entry_source, manifest::DevManifestContentSource, next_config::load_next_config,
     next_image::NextImageContentSourceVc, pages_structure::find_pages_structure,
     router_source::NextRouterContentSourceVc, source_map::NextSourceMapTraceContentSourceVc,
+    JsonIssueReporterVc, NextFontCheckProcessorVc,
 };
 use owo_colors::OwoColorize;
 use turbo_binding::{
@@ -48,7 +49,7 @@ use turbo_binding::{
             source::{
                 combined::CombinedContentSourceVc, router::RouterContentSource,
                 source_maps::SourceMapContentSourceVc, static_assets::StaticAssetsContentSourceVc,
-                ContentSourceVc,
+                wrapping_source::WrappedContentSourceVc, ContentSourceVc,
             },
             DevServer, DevServerBuilder,
         },
@@ -386,7 +387,13 @@ async fn source(
     }
     .cell()
     .into();
-    let main_source = main_source.into();
+    // Checks the fonts of each served chunk as a whole, e.g. warning when more
+    // fonts are used than allowed by `max_fonts`
+    let main_source = WrappedContentSourceVc::new(
+        main_source.into(),
+        NextFontCheckProcessorVc::new(project_path).into(),
+    )
+    .into();
     let source_maps = SourceMapContentSourceVc::new(main_source).into();
     let source_map_trace = NextSourceMapTraceContentSourceVc::new(main_source).into();
     let img_source = NextImageContentSourceVc::new(main_source).into();
@@ -483,6 +490,10 @@ pub async fn start_server(options: &DevServerOptions) -> Result<()> {
         server = server.allow_retry(options.allow_retry);
     }
 
+    if options.json_diagnostics {
+        server = server.issue_reporter(Box::new(|| JsonIssueReporterVc::new().into()));
+    }
+
     let server = server.build().await?;
 
     {
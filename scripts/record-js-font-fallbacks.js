@@ -0,0 +1,140 @@
+/**
+ * Records the fallbacks upstream's JS implementation computes for a set of
+ * fonts, for next-core's Rust port of it to be tested against:
+ * `calculateSizeAdjustValues` of packages/next/src/server/font-utils.ts, as
+ * used by `getFallbackFontOverrideMetrics` of @next/font/google.
+ *
+ * Usage: node scripts/record-js-font-fallbacks.js
+ */
+const fs = require('fs')
+const path = require('path')
+
+const OUTPUT = path.join(
+  __dirname,
+  '../packages/next-swc/crates/next-core/src/next_font/google/fixtures/js-fallbacks.json'
+)
+
+// [family, category, ascent, descent, lineGap, unitsPerEm, xWidthAvg], in
+// the capsize metrics format of next/dist/server/capsize-font-metrics.json
+const FONTS = [
+  ['Inter', 'sans-serif', 2728, -680, 0, 2816, 1335],
+  ['Roboto', 'sans-serif', 1900, -500, 0, 2048, 911],
+  ['Open Sans', 'sans-serif', 2189, -600, 0, 2048, 1063],
+  ['Lato', 'sans-serif', 1974, -426, 0, 2000, 1001],
+  ['Montserrat', 'sans-serif', 968, -251, 0, 1000, 554],
+  ['Poppins', 'sans-serif', 1050, -350, 100, 1000, 548],
+  ['Roboto Slab', 'serif', 2146, -555, 0, 2048, 969],
+  ['Lora', 'serif', 1006, -274, 0, 1000, 468],
+  ['Merriweather', 'serif', 984, -273, 0, 1000, 546],
+  ['Playfair Display', 'serif', 1082, -251, 0, 1000, 482],
+  ['Roboto Mono', 'monospace', 2146, -555, 0, 2048, 1229],
+  ['Source Code Pro', 'monospace', 984, -273, 0, 1000, 600],
+  ['Fira Code', 'monospace', 1050, -350, 0, 1000, 600],
+  ['JetBrains Mono', 'monospace', 1020, -300, 0, 1000, 600],
+]
+
+function readSource(file) {
+  return fs.readFileSync(path.join(__dirname, '..', file), 'utf8')
+}
+
+function readConstant(name) {
+  const constants = readSource('packages/next/src/shared/lib/constants.ts')
+  return JSON.parse(
+    constants
+      .match(new RegExp(`export const ${name} = (\\{[^}]*\\})`))[1]
+      .replace(/(\w+):/g, '"$1":')
+      .replace(/'/g, '"')
+      .replace(/,(\s*\})/, '$1')
+  )
+}
+
+// font-utils.ts can't be required as is, as it's TypeScript and reads the
+// metrics of next/dist, so evaluate the functions computing the fallback
+// from its source, with their type annotations stripped, instead.
+function loadFontUtils(capsizeFontsMetrics) {
+  const fontUtils = readSource('packages/next/src/server/font-utils.ts')
+  const functions = [
+    'formatName',
+    'formatOverrideValue',
+    'calculateSizeAdjustValues',
+  ]
+    .map((name) => {
+      const start = fontUtils.search(
+        new RegExp(`^(export )?function ${name}\\(`, 'm')
+      )
+      const end = fontUtils.indexOf('\n}\n', start) + 2
+      return fontUtils
+        .slice(start, end)
+        .replace(/^export /, '')
+        .replace(/\((\w+): \w+\)(: \w+)?/, '($1)')
+    })
+    .join('\n')
+
+  return new Function(
+    'capsizeFontsMetrics',
+    'DEFAULT_SERIF_FONT',
+    'DEFAULT_SANS_SERIF_FONT',
+    `${functions}\nreturn { formatName, calculateSizeAdjustValues }`
+  )(
+    capsizeFontsMetrics,
+    readConstant('DEFAULT_SERIF_FONT'),
+    readConstant('DEFAULT_SANS_SERIF_FONT')
+  )
+}
+
+const capsizeFontsMetrics = {}
+const { formatName, calculateSizeAdjustValues } = loadFontUtils(
+  capsizeFontsMetrics
+)
+
+const metrics = {}
+for (const [
+  familyName,
+  category,
+  ascent,
+  descent,
+  lineGap,
+  unitsPerEm,
+  xWidthAvg,
+] of FONTS) {
+  metrics[formatName(familyName)] = {
+    familyName,
+    category,
+    capHeight: 0,
+    ascent,
+    descent,
+    lineGap,
+    unitsPerEm,
+    xHeight: 0,
+    xWidthAvg,
+  }
+}
+Object.assign(capsizeFontsMetrics, metrics)
+// The default fallback fonts, with the average widths next-core uses for them
+for (const name of ['DEFAULT_SERIF_FONT', 'DEFAULT_SANS_SERIF_FONT']) {
+  const font = readConstant(name)
+  capsizeFontsMetrics[formatName(font.name)] = {
+    xWidthAvg: font.azAvgWidth,
+    unitsPerEm: font.unitsPerEm,
+  }
+}
+
+const fallbacks = FONTS.map(([font]) => {
+  const { ascent, descent, lineGap, fallbackFont, sizeAdjust } =
+    calculateSizeAdjustValues(font)
+  // As formatted by getFallbackFontOverrideMetrics
+  return {
+    font,
+    fallbackFont,
+    ascentOverride: `${ascent}%`,
+    descentOverride: `${descent}%`,
+    lineGapOverride: `${lineGap}%`,
+    sizeAdjust: `${sizeAdjust}%`,
+  }
+})
+
+fs.mkdirSync(path.dirname(OUTPUT), { recursive: true })
+fs.writeFileSync(
+  OUTPUT,
+  JSON.stringify({ metrics, fallbacks }, null, 2) + '\n'
+)